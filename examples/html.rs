@@ -10,7 +10,7 @@ fn main() {
     </p>
 </body>"#;
 
-    let classes = get_classes(&body);
+    let classes = get_classes(body);
 
     let z = Zephyr::new();
     let css = z.generate_classes(classes.iter().map(String::as_str));
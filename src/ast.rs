@@ -0,0 +1,178 @@
+use std::ops::Range;
+
+use crate::{
+    class::{Class, ValueType},
+    media_queries::{supports, Responsive},
+    modifiers::selector_template,
+    Zephyr,
+};
+
+/// a parsed class, as returned by [`Zephyr::parse`](crate::Zephyr::parse)
+///
+/// every component borrows from the original class and carries its byte span into it,
+/// so it can be used by linters, editors, and formatters
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct ParsedClass<'a> {
+    /// the whole class, as written
+    pub original: &'a str,
+    pub property: Spanned<&'a str>,
+    pub value: Option<Value<'a>>,
+    pub modifiers: ParsedModifiers<'a>,
+    /// the pseudo-element, without the leading `$`
+    pub pseudo: Option<Spanned<&'a str>>,
+    /// whether the class has a leading or trailing `!`
+    pub important: bool,
+    /// whether the property has a leading `-` that negates the value
+    pub negative: bool,
+    /// whether the class defines a custom property, like `set(brand)[#f00]` or `--brand[#f00]`.
+    /// `property` is the name, without the `--`
+    pub custom_property: bool,
+}
+
+/// a piece of a class, along with its byte span in the original class
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Spanned<T> {
+    pub node: T,
+    pub span: Range<usize>,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Value<'a> {
+    /// the value, without the surrounding brackets
+    pub text: &'a str,
+    /// decided by the outermost brackets
+    pub kind: ValueType,
+    /// span of `text`. the brackets are right outside of it
+    pub span: Range<usize>,
+}
+
+/// modifiers of a class, split by kind. each list is in the order they were written in
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct ParsedModifiers<'a> {
+    /// eg: `md`, `<lg`, `@xl`
+    pub responsive: Vec<Spanned<&'a str>>,
+    /// modifiers from [`Zephyr::media_features`], eg: `dark` or `motion-reduce`
+    pub media_features: Vec<Spanned<&'a str>>,
+    /// eg: `@c-md`, `cq-min-[400px]` or `cq-sidebar-md`
+    pub containers: Vec<Spanned<&'a str>>,
+    /// eg: `supports-grid` or `supports-[display:grid]`
+    pub supports: Vec<Spanned<&'a str>>,
+    /// arbitrary selectors, eg: `[&>p]` or `[.js_&]`
+    pub selectors: Vec<Spanned<&'a str>>,
+    /// everything else, eg: `hover`, `odd` or `group-hover`
+    pub pseudo_classes: Vec<Spanned<&'a str>>,
+}
+
+impl<'a> ParsedClass<'a> {
+    pub(crate) fn new(class: &Class<'a>, z: &Zephyr) -> Self {
+        let original = class.original;
+        let spanned = |node: &'a str| Spanned {
+            span: span_of(original, node),
+            node,
+        };
+
+        let mut modifiers = ParsedModifiers::default();
+        for m in &class.modifiers.all {
+            let list = if Responsive::from_str(m, z).is_some() {
+                &mut modifiers.responsive
+            } else if z.media_features.contains_key(*m) {
+                &mut modifiers.media_features
+            } else if Responsive::container(m, z).is_some() {
+                &mut modifiers.containers
+            } else if supports(m, z).is_some() {
+                &mut modifiers.supports
+            } else if selector_template(m).is_some() {
+                &mut modifiers.selectors
+            } else {
+                &mut modifiers.pseudo_classes
+            };
+            list.push(spanned(m));
+        }
+
+        Self {
+            original,
+            property: spanned(class.property),
+            value: class.value.map(|text| Value {
+                text,
+                kind: class.value_type,
+                span: span_of(original, text),
+            }),
+            modifiers,
+            pseudo: class.pseudo.map(spanned),
+            important: class.important,
+            negative: class.negative,
+            custom_property: class.custom_property,
+        }
+    }
+}
+
+/// byte span of `part` inside of `original`
+///
+/// `part` must be a subslice of `original`, which is the case for everything `parse_class` returns
+pub(crate) fn span_of(original: &str, part: &str) -> Range<usize> {
+    let start = part.as_ptr() as usize - original.as_ptr() as usize;
+    debug_assert!(start + part.len() <= original.len());
+    start..start + part.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spanned(node: &str, span: Range<usize>) -> Spanned<&str> {
+        Spanned { node, span }
+    }
+
+    #[test]
+    fn parse_exposes_spans() {
+        let z = Zephyr::new();
+
+        let class = "c[red]hover,md,motion-safe$after";
+        let p = z.parse(class).unwrap();
+        assert_eq!(p.property, spanned("c", 0..1));
+        assert_eq!(
+            p.value,
+            Some(Value {
+                text: "red",
+                kind: ValueType::Normal,
+                span: 2..5,
+            })
+        );
+        assert_eq!(p.modifiers.pseudo_classes, [spanned("hover", 6..11)]);
+        assert_eq!(p.modifiers.responsive, [spanned("md", 12..14)]);
+        assert_eq!(p.modifiers.media_features, [spanned("motion-safe", 15..26)]);
+        assert_eq!(p.pseudo, Some(spanned("after", 27..32)));
+
+        for s in [&p.property, p.pseudo.as_ref().unwrap()] {
+            assert_eq!(&class[s.span.clone()], s.node);
+        }
+    }
+
+    #[test]
+    fn parse_no_value_spans() {
+        let z = Zephyr::new();
+
+        let p = z.parse("flex|hover,<lg").unwrap();
+        assert_eq!(p.property, spanned("flex", 0..4));
+        assert_eq!(p.value, None);
+        assert_eq!(p.modifiers.pseudo_classes, [spanned("hover", 5..10)]);
+        assert_eq!(p.modifiers.responsive, [spanned("<lg", 11..14)]);
+        assert_eq!(p.pseudo, None);
+    }
+
+    #[test]
+    fn parse_unicode_spans() {
+        let z = Zephyr::new();
+
+        let p = z.parse("he🥰llo(my-var)").unwrap();
+        assert_eq!(p.property, spanned("he🥰llo", 0..9));
+        assert_eq!(
+            p.value,
+            Some(Value {
+                text: "my-var",
+                kind: ValueType::Variable,
+                span: 10..16,
+            })
+        );
+    }
+}
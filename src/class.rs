@@ -1,10 +1,13 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, fmt::Write};
 
 use crate::{
+    ast::span_of,
+    consts::{FUNCTIONAL_PSEUDO_CLASSES, PSEUDO_CLASSES, PSEUDO_ELEMENTS},
+    error::suggest,
     indent,
-    media_queries::{wrap_in_query, ReducedMotion, Responsive},
-    modifiers::Modifiers,
-    nl, space, Zephyr, ZephyrError,
+    media_queries::{group_rules, is_media_type, ContainerQuery, QueryOrder, Rule, ViewportRange},
+    modifiers::{is_at_rule, selector_template, Modifiers, Relation, Relational},
+    nl, space, Zephyr, ZephyrError, ZephyrErrorKind,
 };
 
 #[derive(PartialEq, Debug)]
@@ -17,10 +20,16 @@ pub(crate) struct Class<'a> {
     /// the original unparsed value
     /// needed to generate the css selector
     pub original: &'a str,
+    /// whether the declarations get `!important`, eg: `c[red]!` or `!c[red]`
+    pub important: bool,
+    /// whether the value is negated, eg: `-mt[1rem]`
+    pub negative: bool,
+    /// whether the property is the name of a custom property, eg: `brand` in `set(brand)[#f00]` or `--brand[#f00]`
+    pub custom_property: bool,
 }
 
-#[derive(PartialEq, Debug)]
-pub(crate) enum ValueType {
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum ValueType {
     /// replacements will be performed
     ///
     /// eg: `m[1rem]`
@@ -36,7 +45,7 @@ pub(crate) enum ValueType {
 }
 
 impl<'a> Class<'a> {
-    pub(crate) fn selector(&self, z: &Zephyr) -> String {
+    pub(crate) fn selector(&self, z: &Zephyr) -> Result<String, ZephyrError> {
         let Class {
             modifiers,
             pseudo,
@@ -44,114 +53,463 @@ impl<'a> Class<'a> {
             ..
         } = self;
 
-        let mut rest = modifiers
-            .all
-            .iter()
-            .filter(|m| Responsive::from_str(*m).is_none() && ReducedMotion::from_str(*m).is_none())
-            .map(|m| -> &str { z.modifiers.get(*m).map(AsRef::as_ref).unwrap_or(m) })
-            .collect::<Vec<_>>()
-            .join(":");
+        // modifiers that depend on other elements, grouped by the element they point to
+        let mut relatives: Vec<(Relational, Vec<Cow<str>>)> = vec![];
+        let mut pseudo_classes = vec![];
+        let mut templates = vec![];
+        for m in modifiers.all.iter().filter(|m| !is_at_rule(m, z)) {
+            if let Some(t) = selector_template(m) {
+                templates.push(t);
+                continue;
+            }
+            match Relational::from_str(m).filter(|_| !z.modifiers.contains_key(*m)) {
+                Some(r) => {
+                    let pc = self.pseudo_class(z, r.modifier)?;
+                    match relatives
+                        .iter_mut()
+                        .find(|(o, _)| o.relation == r.relation && o.name == r.name)
+                    {
+                        Some((_, pcs)) => pcs.push(pc),
+                        None => relatives.push((r, vec![pc])),
+                    }
+                }
+                None => pseudo_classes.push(self.pseudo_class(z, m)?),
+            }
+        }
+
+        let rest = join_pseudo_classes(&pseudo_classes);
 
+        // groups go first, since peers must be siblings of the element itself
+        relatives.sort_by_key(|(r, _)| r.relation);
+        let mut r = String::new();
+        for (relative, pcs) in relatives {
+            let combinator = match relative.relation {
+                Relation::Group => " ".to_string(),
+                Relation::Peer => format!("{0}~{0}", space(z.pretty_print)),
+            };
+            r.push_str(&format!(
+                ".{}{}{combinator}",
+                escape(&relative.marker()),
+                join_pseudo_classes(&pcs)
+            ));
+        }
+
+        r.push('.');
+        r.push_str(&escape(original));
+        r.push_str(&rest);
+
+        // each template wraps the selector built so far
+        for t in templates {
+            r = replace_underscores(t).replace('&', &r);
+        }
+
+        // the pseudo-element has to be at the end of the selector
         if let Some(pseudo) = pseudo {
-            rest.push_str("::");
-            let pseudo: &str = z.pseudos.get(*pseudo).map(AsRef::as_ref).unwrap_or(pseudo);
-            rest.push_str(pseudo);
+            r.push_str("::");
+            r.push_str(self.pseudo_element(z, pseudo)?);
+        }
+
+        Ok(r)
+    }
+
+    /// resolves a modifier into a pseudo-class, checking that it exists
+    ///
+    /// modifiers like `nth-[3n+1]` fill in the `{}` of their replacement, in this case `nth-child({})`
+    fn pseudo_class<'b>(&self, z: &'b Zephyr, m: &'b str) -> Result<Cow<'b, str>, ZephyrError> {
+        let template = parametric(m).and_then(|(name, arg)| {
+            let t = z.modifiers.get(name).filter(|t| t.contains("{}"))?;
+            Some((t, arg))
+        });
+        if let Some((t, arg)) = template {
+            if arg.is_empty() {
+                return Err(self.error(m, ZephyrErrorKind::MissingArgument));
+            }
+            Ok(t.replace("{}", &replace_underscores(arg)).into())
+        } else if let Some(m) = z.modifiers.get(m).filter(|t| !t.contains("{}")) {
+            Ok(m.into())
+        } else if z.modifiers.contains_key(m) || missing_argument(m) {
+            // like `nth` or `not`, which only make sense with an argument
+            Err(self.error(m, ZephyrErrorKind::MissingArgument))
+        } else if is_known(m, &PSEUDO_CLASSES) {
+            Ok(m.into())
+        } else {
+            let candidates = z
+                .modifiers
+                .keys()
+                .chain(z.breakpoints.iter().map(|b| &b.name))
+                .chain(z.media_features.keys())
+                .map(AsRef::as_ref)
+                .chain(PSEUDO_CLASSES);
+            let err = self
+                .error(m, ZephyrErrorKind::UnknownModifier)
+                .with_suggestion(suggest(m, candidates));
+            if z.strict || !is_name(m) {
+                return Err(err);
+            }
+            tracing::warn!("{err}, using it as a pseudo-class");
+            Ok(m.into())
         }
+    }
 
-        if !rest.is_empty() {
-            rest.insert(0, ':')
+    /// resolves a pseudo-element, checking that it exists
+    fn pseudo_element<'b>(&self, z: &'b Zephyr, p: &'b str) -> Result<&'b str, ZephyrError> {
+        if let Some(p) = z.pseudos.get(p) {
+            Ok(p)
+        } else if is_known(p, &PSEUDO_ELEMENTS) {
+            Ok(p)
+        } else {
+            let candidates = z.pseudos.keys().map(AsRef::as_ref).chain(PSEUDO_ELEMENTS);
+            let err = self
+                .error(p, ZephyrErrorKind::UnknownPseudo)
+                .with_suggestion(suggest(p, candidates));
+            if z.strict || !is_name(p) {
+                return Err(err);
+            }
+            tracing::warn!("{err}, using it as a pseudo-element");
+            Ok(p)
         }
+    }
 
-        let mut r = format!("{original}{rest}")
-            .replace('[', "\\[")
-            .replace(']', "\\]")
-            .replace('|', "\\|")
-            .replace('(', "\\(")
-            .replace(')', "\\)")
-            .replace('{', "\\{")
-            .replace('}', "\\}")
-            .replace('.', "\\.")
-            .replace('#', "\\#")
-            .replace('$', "\\$")
-            .replace('\'', "\\'")
-            .replace('*', "\\*")
-            .replace('<', "\\<")
-            .replace('@', "\\@")
-            .replace('%', "\\%");
-        r.insert(0, '.');
-        r
+    /// builds an error pointing at `part`, which must be a piece of this class
+    fn error(&self, part: &str, kind: ZephyrErrorKind) -> ZephyrError {
+        ZephyrError::new(self.original, span_of(self.original, part), kind)
     }
 
     /// generates the css rule for this class
     /// does not generate the corresponding media query
     pub(crate) fn generate(&self, z: &Zephyr, indent_level: usize) -> Result<String, ZephyrError> {
-        let property = z
-            .properties
-            .get(self.property)
-            .map(AsRef::as_ref)
-            .unwrap_or(self.property);
-        let selector = self.selector(z);
+        let property: Cow<str> = if self.custom_property {
+            format!("--{}{}", z.variable_prefix, self.property).into()
+        } else {
+            z.properties
+                .get(self.property)
+                .map_or(self.property, AsRef::as_ref)
+                .into()
+        };
+        let property = property.as_ref();
+        let selector = self.selector(z)?;
 
         let space = space(z.pretty_print);
         let indent2 = indent(z.pretty_print, indent_level + 1);
         let indent = indent(z.pretty_print, indent_level);
         let nl = nl(z.pretty_print);
 
-        if let Some(val) = self.value {
+        let declarations = if let Some(val) = self.value {
             let val = match self.value_type {
                 ValueType::Normal => {
-                    let v = z
+                    if let Some(v) = z
                         .context_aware_values
                         .get(property)
                         .and_then(|h| h.get(val))
-                        .or_else(|| z.values.get(val))
-                        .map(AsRef::as_ref)
-                        .unwrap_or(val);
-                    replace_underscores(v)
+                    {
+                        replace_underscores(v)
+                    } else if let Some(v) = z.values.get(val) {
+                        // value short-hands take precedence over the theme, like `full` in `border-radius[full]`
+                        replace_underscores(v)
+                    } else if let Some(v) = z.theme.resolve(property, val, &z.variable_prefix) {
+                        v
+                    } else {
+                        replace_underscores(val)
+                    }
                 }
                 ValueType::Literal => val.into(),
-                ValueType::Variable => format!("var(--{val})").into(),
+                ValueType::Variable => variable(val, &z.variable_prefix).into(),
+            };
+            let val = if self.negative {
+                let Some(negated) = negate(&val) else {
+                    let value = self.value.unwrap_or(self.property);
+                    return Err(self.error(value, ZephyrErrorKind::NotNegatable));
+                };
+                negated.into()
+            } else {
+                val
             };
 
-            if let Some(fun) = z.specials.get(property) {
-                let v = fun(&val);
-                Ok(format!(
-                    "{indent}{selector}{space}{{{nl}{indent2}{v}{nl}{indent}}}{nl}",
-                ))
+            if let Some(template) = z.special_templates.get(property) {
+                template.render(&val)
+            } else if let Some(fun) = z.specials.get(property) {
+                fun(&val)
             } else {
-                Ok(format!(
-                    "{indent}{selector}{space}{{{nl}{indent2}{property}:{space}{val}{nl}{indent}}}{nl}"
-                ))
+                format!("{property}:{space}{val}")
             }
         } else if let Some(v) = z.declarations.get(property) {
-            Ok(format!(
-                "{indent}{selector}{space}{{{nl}{indent2}{v}{nl}{indent}}}{nl}"
-            ))
+            v.clone()
+        } else if z.properties.contains_key(self.property)
+            || z.specials.contains_key(self.property)
+            || z.special_templates.contains_key(self.property)
+        {
+            return Err(self.error(self.property, ZephyrErrorKind::ValueMissing));
         } else {
-            Err(ZephyrError::ValueMissing)
-        }
+            let suggestion = suggest(self.property, z.declarations.keys().map(AsRef::as_ref))
+                .or_else(|| {
+                    let properties = z
+                        .properties
+                        .keys()
+                        .chain(z.specials.keys())
+                        .chain(z.special_templates.keys());
+                    suggest(self.property, properties.map(AsRef::as_ref))
+                        .map(|p| format!("{p}[...]"))
+                });
+            return Err(self
+                .error(self.property, ZephyrErrorKind::UnknownDeclaration)
+                .with_suggestion(suggestion));
+        };
+
+        let declarations = if self.important {
+            important(&declarations, space)
+        } else {
+            declarations
+        };
+
+        Ok(format!(
+            "{indent}{selector}{space}{{{nl}{indent2}{declarations}{nl}{indent}}}{nl}"
+        ))
     }
 
     pub fn generate_with_media_query(&self, z: &Zephyr) -> Result<String, ZephyrError> {
-        let mut queries: Vec<String> = vec![];
-        if let Some(r) = &self.modifiers.responsive {
-            queries.extend(r.queries());
+        Ok(group_rules(vec![self.rule(z)?], z.pretty_print))
+    }
+
+    /// generates the css rule for this class, along with the media queries it needs
+    pub(crate) fn rule(&self, z: &Zephyr) -> Result<Rule, ZephyrError> {
+        let mut responsive = ViewportRange::default();
+        for (m, r) in self.modifiers.responsive(z) {
+            if !responsive.add(&r) {
+                return Err(self.error(m, ZephyrErrorKind::ContradictoryModifiers));
+            }
         }
-        if let Some(r) = &self.modifiers.reduced_motion {
-            queries.extend(r.queries().iter().map(ToString::to_string));
+        let features = self.modifiers.media_features(z);
+
+        // media types have to go first
+        let (types, features): (Vec<_>, Vec<_>) =
+            features.into_iter().partition(|f| is_media_type(f));
+        let mut queries = types.clone();
+        queries.extend(responsive.queries().into_iter().map(|q| format!("({q})")));
+        queries.extend(features.iter().cloned());
+
+        // all the container queries have to be for the same container
+        let mut container: Option<(Option<&str>, ViewportRange)> = None;
+        for (m, name, r) in self.modifiers.containers(z) {
+            let (n, range) = container.get_or_insert((name, ViewportRange::default()));
+            if *n != name || !range.add(&r) {
+                return Err(self.error(m, ZephyrErrorKind::ContradictoryModifiers));
+            }
         }
+        let container = container.map(|(name, range)| ContainerQuery::new(name, &range));
 
-        let css = self.generate(z, queries.is_empty().then_some(0).unwrap_or(1))?;
+        let supports = self.modifiers.supports(z);
 
-        Ok(wrap_in_query(css, &queries, z.pretty_print))
+        let indent_level = usize::from(!queries.is_empty())
+            + usize::from(!supports.is_empty())
+            + usize::from(container.is_some());
+        let css = self.generate(z, indent_level)?;
+
+        Ok(Rule {
+            css,
+            order: QueryOrder::new(
+                &responsive,
+                [types, features].concat(),
+                queries,
+                supports,
+                container,
+            ),
+        })
     }
 }
 
+/// whether `name` is in `list`, ignoring the arguments of functional pseudo-classes.
+/// vendor-prefixed names are always accepted
+fn is_known(name: &str, list: &[&str]) -> bool {
+    let name = name.split_once('(').map(|(n, _)| n).unwrap_or(name);
+    name.starts_with('-') || list.contains(&name)
+}
+
+/// whether `m` is a functional pseudo-class without its arguments, like `not` or `has()`
+fn missing_argument(m: &str) -> bool {
+    match m.split_once('(') {
+        Some((name, args)) => {
+            FUNCTIONAL_PSEUDO_CLASSES.contains(&name)
+                && args.strip_suffix(')').is_some_and(|a| a.trim().is_empty())
+        }
+        None => FUNCTIONAL_PSEUDO_CLASSES.contains(&m),
+    }
+}
+
+/// whether a modifier looks like the name of a pseudo-class or pseudo-element, like `open` or `state(x)`,
+/// as opposed to something like `[p]` or `meow-[1]`
+fn is_name(name: &str) -> bool {
+    let name = name.split_once('(').map_or(name, |(n, _)| n);
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+/// turns the value of a variable class into `var()`, eg: `brand,red` becomes `var(--brand, red)`
+///
+/// a fallback in parenthesis is another variable, so `brand,(accent,red)` becomes `var(--brand, var(--accent, red))`
+fn variable(val: &str, prefix: &str) -> String {
+    let Some((name, fallback)) = split_fallback(val) else {
+        return format!("var(--{prefix}{val})");
+    };
+    let fallback = match fallback.strip_prefix('(').and_then(|f| f.strip_suffix(')')) {
+        Some(f) => variable(f, prefix),
+        None => replace_underscores(fallback).into_owned(),
+    };
+    format!("var(--{prefix}{name}, {fallback})")
+}
+
+/// splits a variable at the first comma that isn't inside of brackets
+fn split_fallback(val: &str) -> Option<(&str, &str)> {
+    let mut depth = 0usize;
+    for (i, c) in val.char_indices() {
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => return Some((&val[..i], &val[i + 1..])),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// negates a value, eg: `1rem` becomes `-1rem` and `var(--x)` becomes `calc(-1 * var(--x))`
+///
+/// every comma-separated argument is negated, so specials like `inset-xy[1rem,2rem]` are too.
+/// returns `None` if a part isn't a number, a length, or a function, like `auto`
+fn negate(val: &str) -> Option<String> {
+    let negate_part = |v: &str| {
+        if v.contains('(') {
+            // functions can't just get a `-` in front of them
+            Some(format!("calc(-1 * {v})"))
+        } else if let Some(v) = v.strip_prefix('-') {
+            is_number(v).then(|| v.to_string())
+        } else {
+            is_number(v).then(|| format!("-{v}"))
+        }
+    };
+    let args = split_top_level(val, ',')
+        .into_iter()
+        .map(|arg| {
+            split_top_level(arg.trim(), ' ')
+                .into_iter()
+                .map(negate_part)
+                .collect::<Option<Vec<_>>>()
+                .map(|parts| parts.join(" "))
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(args.join(","))
+}
+
+/// whether a value starts like a number, eg: `1rem`, `.5` or `50%`
+fn is_number(v: &str) -> bool {
+    let v = v.strip_prefix('.').unwrap_or(v);
+    v.starts_with(|c: char| c.is_ascii_digit())
+}
+
+/// adds `!important` to every declaration in a list like `margin-left:1rem;margin-right:1rem`
+fn important(declarations: &str, space: &str) -> String {
+    split_top_level(declarations, ';')
+        .into_iter()
+        .filter(|d| !d.trim().is_empty())
+        .map(|d| format!("{d}{space}!important"))
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+/// splits by `sep`, except when it's inside of brackets or quotes,
+/// like the `;` in `url(data:image/png;base64,AAA)` or `content:";"`
+pub(crate) fn split_top_level(s: &str, sep: char) -> Vec<&str> {
+    let mut parts = vec![];
+    let mut depth = 0usize;
+    let mut quote = None;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, '(' | '[' | '{') => depth += 1,
+            (None, ')' | ']' | '}') => depth = depth.saturating_sub(1),
+            (None, c) if c == sep && depth == 0 => {
+                parts.push(&s[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&s[start..]);
+    parts
+}
+
+/// joins pseudo-classes and attribute selectors, which don't need a `:` before them
+fn join_pseudo_classes(pcs: &[Cow<str>]) -> String {
+    pcs.iter()
+        .map(|pc| {
+            if pc.starts_with('[') {
+                pc.to_string()
+            } else {
+                format!(":{pc}")
+            }
+        })
+        .collect()
+}
+
+/// splits a modifier like `nth-[3n+1]` into its name and argument
+fn parametric(m: &str) -> Option<(&str, &str)> {
+    m.strip_suffix(']')?.split_once("-[")
+}
+
 /// replaces underscores with spaces
-fn replace_underscores(s: &str) -> Cow<str> {
+fn replace_underscores(s: &str) -> Cow<'_, str> {
     if s.contains('_') {
         s.replace('_', " ").into()
     } else {
         s.into()
     }
 }
+
+/// escapes a class name so it can be used in a css selector, like `CSS.escape` does
+///
+/// see <https://drafts.csswg.org/cssom/#serialize-an-identifier>
+fn escape(s: &str) -> String {
+    let mut r = String::with_capacity(s.len() * 2);
+    let first = s.chars().next();
+
+    for (i, c) in s.chars().enumerate() {
+        match c {
+            '\0' => r.push('\u{FFFD}'),
+            // identifiers can't start with a digit, or with a hyphen followed by a digit
+            '0'..='9' if i == 0 || (i == 1 && first == Some('-')) => {
+                let _ = write!(r, "\\{:x} ", c as u32);
+            }
+            '\u{1}'..='\u{1F}' | '\u{7F}' => {
+                let _ = write!(r, "\\{:x} ", c as u32);
+            }
+            '-' if i == 0 && s.len() == 1 => r.push_str("\\-"),
+            '-' | '_' | '0'..='9' | 'a'..='z' | 'A'..='Z' => r.push(c),
+            c if !c.is_ascii() => r.push(c),
+            _ => {
+                r.push('\\');
+                r.push(c);
+            }
+        }
+    }
+    r
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_like_css() {
+        assert_eq!(escape("m[1rem]"), r"m\[1rem\]");
+        assert_eq!(escape("w[1/2]"), r"w\[1\/2\]");
+        assert_eq!(escape("grid-area[1/1/2/2]"), r"grid-area\[1\/1\/2\/2\]");
+        assert_eq!(escape("c[red]!"), r"c\[red\]\!");
+        assert_eq!(escape("he🥰llo[a b]"), r"he🥰llo\[a\ b\]");
+        assert_eq!(escape("1a"), r"\31 a");
+        assert_eq!(escape("-1a"), r"-\31 a");
+        assert_eq!(escape("-"), r"\-");
+        assert_eq!(escape("--a"), "--a");
+        assert_eq!(escape("a\u{1}b\0"), "a\\1 b\u{FFFD}");
+    }
+}
@@ -0,0 +1,463 @@
+//! declarative configuration for [`Zephyr`], from toml or json files
+//!
+//! ```toml
+//! extends = "default"
+//!
+//! [properties]
+//! bd = "border"
+//!
+//! [specials]
+//! mx = "margin-left:{val};margin-right:{val}"
+//!
+//! [breakpoints]
+//! tablet = "900px"
+//!
+//! [theme.scales.color]
+//! brand-500 = "#3b82f6"
+//!
+//! [remove]
+//! breakpoints = ["xxl"]
+//! ```
+
+use std::{collections::HashMap, fmt, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Breakpoint, Length, SpecialTemplate, Zephyr};
+
+/// the contents of a config file
+///
+/// everything is optional. the entries are added on top of the ones from `extends`,
+/// after taking out the ones in `remove`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// what to start from
+    pub extends: Extends,
+    pub declarations: HashMap<String, String>,
+    pub properties: HashMap<String, String>,
+    pub values: HashMap<String, String>,
+    pub context_aware_values: HashMap<String, HashMap<String, String>>,
+    pub modifiers: HashMap<String, String>,
+    pub pseudos: HashMap<String, String>,
+    /// name -> width, like `tablet = "900px"`
+    pub breakpoints: HashMap<String, String>,
+    /// name -> template, like `sq = "width:{val};height:{val}"`. see [`SpecialTemplate`]
+    pub specials: HashMap<String, SpecialTemplate>,
+    /// modifier -> media condition, like `hover-hover = "(hover:hover)"`
+    pub media_features: HashMap<String, String>,
+    /// name -> condition for `@supports`, like `grid = "(display:grid)"`
+    pub supports: HashMap<String, String>,
+    /// replaces [`Zephyr::variable_prefix`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variable_prefix: Option<String>,
+    pub theme: ThemeConfig,
+    /// entries to take out of the ones from `extends`
+    pub remove: Removals,
+}
+
+/// additions to [`Zephyr::theme`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThemeConfig {
+    /// scale -> token -> value. the tokens are added to the scales that already exist
+    pub scales: HashMap<String, HashMap<String, String>>,
+    /// property -> scale
+    pub bindings: HashMap<String, String>,
+    /// dark mode tokens, with the same shape as `scales`
+    pub dark: HashMap<String, HashMap<String, String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub use_variables: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dark_selector: Option<String>,
+}
+
+/// the ruleset a config starts from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Extends {
+    /// everything in [`Zephyr::new`]
+    #[default]
+    Default,
+    /// nothing at all
+    Empty,
+}
+
+/// the names of the entries to remove, for each kind of entry
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Removals {
+    pub declarations: Vec<String>,
+    pub properties: Vec<String>,
+    pub values: Vec<String>,
+    /// removes all the values of these properties
+    pub context_aware_values: Vec<String>,
+    pub modifiers: Vec<String>,
+    pub pseudos: Vec<String>,
+    pub breakpoints: Vec<String>,
+    pub specials: Vec<String>,
+    pub media_features: Vec<String>,
+    pub supports: Vec<String>,
+    pub theme: ThemeRemovals,
+}
+
+/// the names of the theme entries to remove
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThemeRemovals {
+    /// removes whole scales, along with their dark mode tokens
+    pub scales: Vec<String>,
+    /// properties that stop using a scale
+    pub bindings: Vec<String>,
+}
+
+/// an error found while loading a config
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(std::io::Error),
+    Toml(toml::de::Error),
+    Json(serde_json::Error),
+    /// the width of a breakpoint isn't a non-negative length in `px`, `em` or `rem`
+    InvalidBreakpoint {
+        name: String,
+        width: String,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(e) => write!(f, "couldn't read the config: {e}"),
+            ConfigError::Toml(e) => write!(f, "invalid config: {e}"),
+            ConfigError::Json(e) => write!(f, "invalid config: {e}"),
+            ConfigError::InvalidBreakpoint { name, width } => write!(
+                f,
+                "the width of breakpoint `{name}` should be a length in px, em or rem, found `{width}`"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Config {
+    pub fn from_toml(s: &str) -> Result<Self, ConfigError> {
+        toml::from_str(s).map_err(ConfigError::Toml)
+    }
+
+    pub fn from_json(s: &str) -> Result<Self, ConfigError> {
+        serde_json::from_str(s).map_err(ConfigError::Json)
+    }
+
+    /// reads a config file, as json if its extension is `.json`, and as toml otherwise
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let s = std::fs::read_to_string(path).map_err(ConfigError::Io)?;
+        if path.extension().is_some_and(|e| e == "json") {
+            Self::from_json(&s)
+        } else {
+            Self::from_toml(&s)
+        }
+    }
+}
+
+impl Zephyr {
+    /// builds a [`Zephyr`] from a config
+    pub fn from_config(config: Config) -> Result<Self, ConfigError> {
+        let mut z = match config.extends {
+            Extends::Default => Zephyr::new(),
+            Extends::Empty => Zephyr::default(),
+        };
+
+        let remove = config.remove;
+        let maps = [
+            (&mut z.declarations, remove.declarations),
+            (&mut z.properties, remove.properties),
+            (&mut z.values, remove.values),
+            (&mut z.modifiers, remove.modifiers),
+            (&mut z.pseudos, remove.pseudos),
+            (&mut z.media_features, remove.media_features),
+            (&mut z.supports, remove.supports),
+            (&mut z.theme.bindings, remove.theme.bindings),
+        ];
+        for (map, names) in maps {
+            for name in names {
+                map.remove(&name);
+            }
+        }
+        for name in remove.context_aware_values {
+            z.context_aware_values.remove(&name);
+        }
+        for name in remove.specials {
+            z.specials.remove(&name);
+            z.special_templates.remove(&name);
+        }
+        for name in remove.theme.scales {
+            z.theme.scales.remove(&name);
+            z.theme.dark.remove(&name);
+        }
+        z.breakpoints
+            .retain(|b| !remove.breakpoints.contains(&b.name));
+
+        z.declarations.extend(config.declarations);
+        z.properties.extend(config.properties);
+        z.values.extend(config.values);
+        z.modifiers.extend(config.modifiers);
+        z.pseudos.extend(config.pseudos);
+        z.media_features.extend(config.media_features);
+        z.supports.extend(config.supports);
+        if let Some(prefix) = config.variable_prefix {
+            z.variable_prefix = prefix;
+        }
+
+        let theme = config.theme;
+        for (scale, tokens) in theme.scales {
+            z.theme.scales.entry(scale).or_default().extend(tokens);
+        }
+        for (scale, tokens) in theme.dark {
+            z.theme.dark.entry(scale).or_default().extend(tokens);
+        }
+        z.theme.bindings.extend(theme.bindings);
+        if let Some(use_variables) = theme.use_variables {
+            z.theme.use_variables = use_variables;
+        }
+        if theme.dark_selector.is_some() {
+            z.theme.dark_selector = theme.dark_selector;
+        }
+        for (property, values) in config.context_aware_values {
+            z.context_aware_values
+                .entry(property)
+                .or_default()
+                .extend(values);
+        }
+        for (name, template) in config.specials {
+            z.add_special(name, template);
+        }
+        for (name, width) in config.breakpoints {
+            let Ok(length) = width.parse::<Length>() else {
+                return Err(ConfigError::InvalidBreakpoint { name, width });
+            };
+            z.breakpoints.retain(|b| b.name != name);
+            z.breakpoints.push(Breakpoint::new(&name, length));
+        }
+
+        Ok(z)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_toml() {
+        let config = Config::from_toml(
+            r##"
+            variable_prefix = "zp-"
+
+            [properties]
+            bd = "border"
+
+            [values]
+            half = "50%"
+
+            [context_aware_values.display]
+            g = "grid"
+
+            [specials]
+            mx = "margin-inline:{val}"
+            sq = "width:{val};height:{val}"
+            inset-xy = "left:{0};right:{0};top:{1};bottom:{1}"
+
+            [breakpoints]
+            md = "800px"
+            tablet = "60em"
+
+            [media_features]
+            wide-gamut = "(color-gamut:p3)"
+
+            [supports]
+            anchor = "(anchor-name:--a)"
+
+            [theme]
+            dark_selector = "[data-theme=dark]"
+
+            [theme.scales.color]
+            brand-500 = "#3b82f6"
+
+            [theme.dark.color]
+            brand-500 = "#60a5fa"
+
+            [theme.bindings]
+            outline-color = "color"
+
+            [remove]
+            declarations = ["flex"]
+            breakpoints = ["xxl"]
+            media_features = ["print"]
+            supports = ["grid"]
+
+            [remove.theme]
+            scales = ["z-index"]
+            bindings = ["margin"]
+            "##,
+        )
+        .unwrap();
+        let mut z = Zephyr::from_config(config).unwrap();
+        z.strict = true;
+
+        assert_eq!(
+            z.generate_classes([
+                "bd[1px_solid]",
+                "w[half]",
+                "display[g]",
+                "mx[1rem]",
+                "sq[2rem]md",
+                "m[0]tablet",
+                "m[0]xxl",
+                "flex",
+                "mt[1rem]",
+                "inset-xy[0,1rem]",
+            ]),
+            [
+                r#".bd\[1px_solid\]{border:1px solid}"#,
+                r#".w\[half\]{width:50%}"#,
+                r#".display\[g\]{display:grid}"#,
+                r#".mx\[1rem\]{margin-inline:1rem}"#,
+                r#".mt\[1rem\]{margin-top:1rem}"#,
+                r#".inset-xy\[0\,1rem\]{left:0;right:0;top:1rem;bottom:1rem}"#,
+                r#"@media(min-width:800px){.sq\[2rem\]md{width:2rem;height:2rem}}"#,
+                r#"@media(min-width:60em){.m\[0\]tablet{margin:0}}"#,
+            ]
+            .concat()
+        );
+
+        assert_eq!(
+            z.generate_classes([
+                "c[red]wide-gamut",
+                "c[red]supports-anchor",
+                "c[red]print",
+                "c[red]supports-grid",
+                "c(x)",
+                "c[brand-500]",
+                "outline-color[brand-500]",
+                "m[4]",
+                "z-index[10]",
+            ]),
+            [
+                r#".c\(x\){color:var(--zp-x)}"#,
+                r#".c\[brand-500\]{color:#3b82f6}"#,
+                r#".outline-color\[brand-500\]{outline-color:#3b82f6}"#,
+                r#".m\[4\]{margin:4}"#,
+                r#".z-index\[10\]{z-index:10}"#,
+                r#"@supports(anchor-name:--a){.c\[red\]supports-anchor{color:red}}"#,
+                r#"@media(color-gamut:p3){.c\[red\]wide-gamut{color:red}}"#,
+            ]
+            .concat()
+        );
+        assert!(z
+            .generate_theme_variables()
+            .ends_with("[data-theme=dark]{--zp-color-brand-500:#60a5fa}"));
+    }
+
+    #[test]
+    fn extends_empty() {
+        let config = Config::from_json(
+            r#"{"extends": "empty", "properties": {"c": "color"}, "breakpoints": {"wide": "1000px"}}"#,
+        )
+        .unwrap();
+        let z = Zephyr::from_config(config).unwrap();
+
+        assert_eq!(z.properties.len(), 1);
+        assert!(z.declarations.is_empty());
+        assert_eq!(z.breakpoints, [Breakpoint::new("wide", Length::px(1000.0))]);
+        assert_eq!(
+            z.generate_classes(["c[red]", "m[0]"]),
+            r#".c\[red\]{color:red}.m\[0\]{m:0}"#
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let config = Config::from_toml(
+            r##"
+            variable_prefix = "zp-"
+
+            [specials]
+            inset-xy = "left:{0};right:{0};top:{1};bottom:{1}"
+
+            [media_features]
+            wide-gamut = "(color-gamut:p3)"
+
+            [supports]
+            anchor = "(anchor-name:--a)"
+
+            [theme]
+            use_variables = true
+            dark_selector = "[data-theme=dark]"
+            bindings = { outline-color = "color" }
+            scales = { color = { brand-500 = "#3b82f6" } }
+            dark = { color = { brand-500 = "#60a5fa" } }
+
+            [remove]
+            media_features = ["print"]
+            supports = ["grid"]
+            theme = { scales = ["z-index"], bindings = ["margin"] }
+            "##,
+        )
+        .unwrap();
+        assert_eq!(config.variable_prefix.as_deref(), Some("zp-"));
+        assert_eq!(config.theme.scales["color"]["brand-500"], "#3b82f6");
+        assert_eq!(config.remove.theme.bindings, ["margin"]);
+        assert_eq!(
+            config.specials["inset-xy"],
+            SpecialTemplate::new("left:{0};right:{0};top:{1};bottom:{1}")
+        );
+
+        let toml = toml::to_string(&config).unwrap();
+        assert_eq!(Config::from_toml(&toml).unwrap(), config);
+        let json = serde_json::to_string(&config).unwrap();
+        assert_eq!(Config::from_json(&json).unwrap(), config);
+
+        // specials added at runtime can be written back to a config
+        let mut z = Zephyr::from_config(config).unwrap();
+        z.add_special("sq", "width:{val};height:{val}");
+        let config = Config {
+            specials: z.special_templates.clone(),
+            ..Config::default()
+        };
+        let toml = toml::to_string(&config).unwrap();
+        let z = Zephyr::from_config(Config::from_toml(&toml).unwrap()).unwrap();
+        assert_eq!(
+            z.generate_classes(["sq[1rem]", "inset-xy[0]"]),
+            r#".sq\[1rem\]{width:1rem;height:1rem}.inset-xy\[0\]{left:0;right:0;top:0;bottom:0}"#
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert!(matches!(
+            Config::from_toml("meow = 1"),
+            Err(ConfigError::Toml(_))
+        ));
+        assert!(matches!(
+            Config::from_toml("extends = \"everything\""),
+            Err(ConfigError::Toml(_))
+        ));
+
+        let config = Config::from_toml("[breakpoints]\nmd = \"wide\"").unwrap();
+        let Err(err) = Zephyr::from_config(config) else {
+            panic!("the breakpoint should be invalid");
+        };
+        assert_eq!(
+            err.to_string(),
+            "the width of breakpoint `md` should be a length in px, em or rem, found `wide`"
+        );
+
+        let config = Config::from_toml("[breakpoints]\nmd = \"-5px\"").unwrap();
+        assert!(matches!(
+            Zephyr::from_config(config),
+            Err(ConfigError::InvalidBreakpoint { .. })
+        ));
+    }
+}
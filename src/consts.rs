@@ -147,3 +147,125 @@ pub const CSS_COLORS: [&str; 147] = [
     "yellow",
     "yellowgreen",
 ];
+
+/// standard pseudo-classes. the functional ones are listed without their arguments
+pub const PSEUDO_CLASSES: [&str; 73] = [
+    "active",
+    "any-link",
+    "autofill",
+    "blank",
+    "buffering",
+    "checked",
+    "closed",
+    "current",
+    "default",
+    "defined",
+    "dir",
+    "disabled",
+    "empty",
+    "enabled",
+    "first",
+    "first-child",
+    "first-of-type",
+    "focus",
+    "focus-visible",
+    "focus-within",
+    "fullscreen",
+    "future",
+    "has",
+    "host",
+    "host-context",
+    "hover",
+    "in-range",
+    "indeterminate",
+    "invalid",
+    "is",
+    "lang",
+    "last-child",
+    "last-of-type",
+    "left",
+    "link",
+    "local-link",
+    "modal",
+    "muted",
+    "not",
+    "nth-child",
+    "nth-col",
+    "nth-last-child",
+    "nth-last-col",
+    "nth-last-of-type",
+    "nth-of-type",
+    "only-child",
+    "only-of-type",
+    "open",
+    "optional",
+    "out-of-range",
+    "past",
+    "paused",
+    "picture-in-picture",
+    "placeholder-shown",
+    "playing",
+    "popover-open",
+    "read-only",
+    "read-write",
+    "required",
+    "right",
+    "root",
+    "scope",
+    "seeking",
+    "stalled",
+    "state",
+    "target",
+    "target-within",
+    "user-invalid",
+    "user-valid",
+    "valid",
+    "visited",
+    "volume-locked",
+    "where",
+];
+
+/// pseudo-classes from [`PSEUDO_CLASSES`] that need an argument, like `not(.a)`
+pub const FUNCTIONAL_PSEUDO_CLASSES: [&str; 14] = [
+    "dir",
+    "has",
+    "host-context",
+    "is",
+    "lang",
+    "not",
+    "nth-child",
+    "nth-col",
+    "nth-last-child",
+    "nth-last-col",
+    "nth-last-of-type",
+    "nth-of-type",
+    "state",
+    "where",
+];
+
+/// standard pseudo-elements. the functional ones are listed without their arguments
+pub const PSEUDO_ELEMENTS: [&str; 23] = [
+    "after",
+    "backdrop",
+    "before",
+    "cue",
+    "cue-region",
+    "details-content",
+    "file-selector-button",
+    "first-letter",
+    "first-line",
+    "grammar-error",
+    "highlight",
+    "marker",
+    "part",
+    "placeholder",
+    "selection",
+    "slotted",
+    "spelling-error",
+    "target-text",
+    "view-transition",
+    "view-transition-group",
+    "view-transition-image-pair",
+    "view-transition-new",
+    "view-transition-old",
+];
@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use crate::SpecialDeclaration;
+use crate::{Breakpoint, Length, SpecialDeclaration, Theme};
 
 fn vec_to_hashmap(v: &[(&str, &str)]) -> HashMap<String, String> {
     v.iter()
@@ -21,6 +21,8 @@ pub(crate) fn default_declarations() -> HashMap<String, String> {
         ("justify-evenly", "justify-content:space-evenly"),
         ("text-left", "text-align:left"),
         ("text-right", "text-align:right"),
+        ("cq", "container-type:inline-size"),
+        ("cq-size", "container-type:size"),
         // TODO
     ])
 }
@@ -47,6 +49,8 @@ pub(crate) fn default_properties() -> HashMap<String, String> {
         ("fw", "font-weight"),
         ("ff", "font-family"),
         ("fs", "font-size"),
+        ("ct", "container-type"),
+        ("cn", "container-name"),
         // TODO
     ])
 }
@@ -116,6 +120,27 @@ pub(crate) fn default_modifiers() -> HashMap<String, String> {
         ("first", "first-child"),
         ("last", "last-child"),
         ("only", "only-child"),
+        // these take an argument, eg: `nth-[3n+1]`
+        ("nth", "nth-child({})"),
+        ("nth-last", "nth-last-child({})"),
+        ("nth-of-type", "nth-of-type({})"),
+        ("nth-last-of-type", "nth-last-of-type({})"),
+        ("not", "not({})"),
+        ("has", "has({})"),
+        ("is", "is({})"),
+        ("where", "where({})"),
+        // attribute selectors, eg: `data-[state=open]` or `aria-expanded`
+        ("data", "[data-{}]"),
+        ("aria", "[aria-{}]"),
+        ("aria-busy", "[aria-busy=\"true\"]"),
+        ("aria-checked", "[aria-checked=\"true\"]"),
+        ("aria-disabled", "[aria-disabled=\"true\"]"),
+        ("aria-expanded", "[aria-expanded=\"true\"]"),
+        ("aria-hidden", "[aria-hidden=\"true\"]"),
+        ("aria-pressed", "[aria-pressed=\"true\"]"),
+        ("aria-readonly", "[aria-readonly=\"true\"]"),
+        ("aria-required", "[aria-required=\"true\"]"),
+        ("aria-selected", "[aria-selected=\"true\"]"),
         // TODO
     ])
 }
@@ -127,10 +152,54 @@ pub(crate) fn default_pseudos() -> HashMap<String, String> {
     ])
 }
 
+pub(crate) fn default_breakpoints() -> Vec<Breakpoint> {
+    [
+        ("sm", 640.0),
+        ("md", 768.0),
+        ("lg", 1024.0),
+        ("xl", 1280.0),
+        ("xxl", 1536.0),
+    ]
+    .into_iter()
+    .map(|(name, width)| Breakpoint::new(name, Length::px(width)))
+    .collect()
+}
+
+pub(crate) fn default_media_features() -> HashMap<String, String> {
+    vec_to_hashmap(&[
+        ("motion-reduce", "(prefers-reduced-motion:reduce)"),
+        ("motion-safe", "(prefers-reduced-motion:no-preference)"),
+        ("dark", "(prefers-color-scheme:dark)"),
+        ("light", "(prefers-color-scheme:light)"),
+        ("contrast-more", "(prefers-contrast:more)"),
+        ("contrast-less", "(prefers-contrast:less)"),
+        ("forced-colors", "(forced-colors:active)"),
+        ("print", "print"),
+        ("portrait", "(orientation:portrait)"),
+        ("landscape", "(orientation:landscape)"),
+        ("can-hover", "(hover:hover)"),
+        ("pointer-coarse", "(pointer:coarse)"),
+        ("pointer-fine", "(pointer:fine)"),
+    ])
+}
+
+pub(crate) fn default_supports() -> HashMap<String, String> {
+    vec_to_hashmap(&[
+        ("grid", "(display:grid)"),
+        ("subgrid", "(grid-template-columns:subgrid)"),
+        (
+            "backdrop",
+            "((backdrop-filter:blur(0)) or (-webkit-backdrop-filter:blur(0)))",
+        ),
+        ("has", "selector(:has(*))"),
+        ("container", "(container-type:inline-size)"),
+    ])
+}
+
 macro_rules! special {
     ($name:literal, $val:ident, $string:literal) => {
         ($name, {
-            fn fun<'a>($val: &'a str) -> String {
+            fn fun($val: &str) -> String {
                 format!($string)
             }
             Box::new(fun) as SpecialDeclaration
@@ -138,6 +207,142 @@ macro_rules! special {
     };
 }
 
+pub(crate) fn default_theme() -> Theme {
+    // multiples of 0.25rem, like `4` -> `1rem`
+    let spacing = [
+        "0.5", "1", "1.5", "2", "2.5", "3", "3.5", "4", "5", "6", "7", "8", "9", "10", "11", "12",
+        "14", "16", "20", "24", "28", "32", "36", "40", "44", "48", "52", "56", "60", "64", "72",
+        "80", "96",
+    ]
+    .into_iter()
+    .map(|n| {
+        (
+            n.to_string(),
+            format!("{}rem", n.parse::<f64>().unwrap() / 4.0),
+        )
+    })
+    .chain([
+        ("0".to_string(), "0".to_string()),
+        ("px".to_string(), "1px".to_string()),
+    ])
+    .collect();
+
+    let scales = [
+        ("spacing", spacing),
+        (
+            "font-size",
+            vec_to_hashmap(&[
+                ("xs", "0.75rem"),
+                ("sm", "0.875rem"),
+                ("base", "1rem"),
+                ("lg", "1.125rem"),
+                ("xl", "1.25rem"),
+                ("2xl", "1.5rem"),
+                ("3xl", "1.875rem"),
+                ("4xl", "2.25rem"),
+                ("5xl", "3rem"),
+            ]),
+        ),
+        (
+            "radius",
+            vec_to_hashmap(&[
+                ("none", "0"),
+                ("sm", "0.125rem"),
+                ("md", "0.375rem"),
+                ("lg", "0.5rem"),
+                ("xl", "0.75rem"),
+                ("full", "9999px"),
+            ]),
+        ),
+        (
+            "shadow",
+            vec_to_hashmap(&[
+                ("sm", "0 1px 2px 0 rgb(0 0 0 / 0.05)"),
+                ("md", "0 4px 6px -1px rgb(0 0 0 / 0.1)"),
+                ("lg", "0 10px 15px -3px rgb(0 0 0 / 0.1)"),
+                ("none", "none"),
+            ]),
+        ),
+        (
+            "z-index",
+            vec_to_hashmap(&[
+                ("0", "0"),
+                ("10", "10"),
+                ("20", "20"),
+                ("30", "30"),
+                ("40", "40"),
+                ("50", "50"),
+            ]),
+        ),
+        // meant to be filled with your own palette, like `brand-500`
+        ("color", HashMap::new()),
+    ]
+    .into_iter()
+    .map(|(name, scale)| (name.to_string(), scale))
+    .collect();
+
+    let bindings = [
+        (
+            "spacing",
+            &[
+                "margin",
+                "margin-top",
+                "margin-bottom",
+                "margin-left",
+                "margin-right",
+                "mx",
+                "my",
+                "padding",
+                "padding-top",
+                "padding-bottom",
+                "padding-left",
+                "padding-right",
+                "px",
+                "py",
+                "gap",
+                "row-gap",
+                "column-gap",
+                "top",
+                "bottom",
+                "left",
+                "right",
+                "inset",
+            ] as &[&str],
+        ),
+        ("font-size", &["font-size"]),
+        ("radius", &["border-radius"]),
+        ("shadow", &["box-shadow"]),
+        ("z-index", &["z-index"]),
+        (
+            "color",
+            &[
+                "color",
+                "background",
+                "background-color",
+                "border-color",
+                "outline-color",
+                "fill",
+                "stroke",
+            ],
+        ),
+    ]
+    .into_iter()
+    .flat_map(|(scale, properties)| {
+        properties
+            .iter()
+            .map(move |p| (p.to_string(), scale.to_string()))
+    })
+    .collect();
+
+    Theme {
+        scales,
+        bindings,
+        use_variables: false,
+        dark: HashMap::new(),
+        dark_selector: None,
+    }
+}
+
 pub(crate) fn default_specials() -> HashMap<String, SpecialDeclaration> {
     [
         special!("mx", val, "margin-left:{val};margin-right:{val}"),
@@ -145,6 +350,7 @@ pub(crate) fn default_specials() -> HashMap<String, SpecialDeclaration> {
         special!("px", val, "padding-left:{val};padding-right:{val}"),
         special!("py", val, "padding-top:{val};padding-bottom:{val}"),
         special!("wh", val, "width:{val};height:{val};"),
+        special!("cq", val, "container-type:inline-size;container-name:{val}"),
         // TODO
     ]
     .into_iter()
@@ -0,0 +1,177 @@
+use std::{fmt, ops::Range};
+
+/// an error found while parsing or generating a class
+///
+/// points at the offending part of the class, and may come with a suggestion
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct ZephyrError {
+    /// the class that failed
+    pub class: String,
+    /// byte span of the offending part of `class`
+    pub span: Range<usize>,
+    pub kind: ZephyrErrorKind,
+    /// what the user probably meant to write, if anything is close enough
+    pub suggestion: Option<String>,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum ZephyrErrorKind {
+    /// an opening bracket is never closed
+    UnclosedBracket(char),
+    /// a closing bracket has no matching opening bracket
+    UnexpectedClosingBracket(char),
+    /// a closing bracket doesn't match the innermost open bracket, eg: `w[calc(100%]`
+    MismatchedBracket { expected: char, found: char },
+    /// a quote inside of a value is never closed
+    UnclosedQuote(char),
+    /// there's nothing before the value or the modifiers, eg: `[red]`
+    EmptyProperty,
+    /// there's an empty modifier, eg: `m[1rem]hover,`
+    EmptyModifier,
+    /// the class has no value, and it isn't one of the `declarations`
+    UnknownDeclaration,
+    /// the class has no value, but the property needs one, eg: `mt`
+    ValueMissing,
+    /// the modifier isn't a known pseudo-class or media query
+    UnknownModifier,
+    /// the pseudo-element isn't a known pseudo-element
+    UnknownPseudo,
+    /// the modifier needs an argument, eg: `not`, `nth-[]` or `data-[]`
+    MissingArgument,
+    /// the modifier can't be combined with the previous ones, eg: `<sm` in `m[0]lg,<sm`
+    ContradictoryModifiers,
+    /// the property is negated, but the value isn't a number, a length, or a function, eg: `-m[auto]`
+    NotNegatable,
+}
+
+impl ZephyrError {
+    pub(crate) fn new(class: &str, span: Range<usize>, kind: ZephyrErrorKind) -> Self {
+        Self {
+            class: class.to_string(),
+            span,
+            kind,
+            suggestion: None,
+        }
+    }
+
+    pub(crate) fn with_suggestion(mut self, suggestion: Option<String>) -> Self {
+        self.suggestion = suggestion;
+        self
+    }
+
+    /// the offending part of the class
+    pub fn snippet(&self) -> &str {
+        self.class.get(self.span.clone()).unwrap_or_default()
+    }
+}
+
+impl fmt::Display for ZephyrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let snippet = self.snippet();
+        match &self.kind {
+            ZephyrErrorKind::UnclosedBracket(c) => write!(f, "unclosed `{c}`")?,
+            ZephyrErrorKind::UnexpectedClosingBracket(c) => write!(f, "unexpected `{c}`")?,
+            ZephyrErrorKind::MismatchedBracket { expected, found } => {
+                write!(f, "expected `{expected}`, found `{found}`")?
+            }
+            ZephyrErrorKind::UnclosedQuote(c) => write!(f, "unclosed quote `{c}`")?,
+            ZephyrErrorKind::EmptyProperty => write!(f, "missing property")?,
+            ZephyrErrorKind::EmptyModifier => write!(f, "empty modifier")?,
+            ZephyrErrorKind::UnknownDeclaration => write!(f, "unknown declaration `{snippet}`")?,
+            ZephyrErrorKind::ValueMissing => write!(f, "`{snippet}` needs a value")?,
+            ZephyrErrorKind::UnknownModifier => write!(f, "unknown modifier `{snippet}`")?,
+            ZephyrErrorKind::UnknownPseudo => write!(f, "unknown pseudo-element `{snippet}`")?,
+            ZephyrErrorKind::MissingArgument => write!(f, "`{snippet}` needs an argument")?,
+            ZephyrErrorKind::ContradictoryModifiers => {
+                write!(f, "`{snippet}` contradicts the previous modifiers")?
+            }
+            ZephyrErrorKind::NotNegatable => write!(f, "`{snippet}` can't be negated")?,
+        }
+        write!(
+            f,
+            " in `{}` at {}..{}",
+            self.class, self.span.start, self.span.end
+        )?;
+        if let Some(s) = &self.suggestion {
+            write!(f, ", did you mean `{s}`?")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ZephyrError {}
+
+/// finds the candidate closest to `name`, if any is close enough to be a likely typo
+pub(crate) fn suggest<'a>(
+    name: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<String> {
+    let max = (name.chars().count() / 3).max(1);
+    candidates
+        .into_iter()
+        .filter(|c| *c != name)
+        .map(|c| (edit_distance(name, c), c))
+        .filter(|(d, _)| *d <= max)
+        // ties are broken alphabetically, so suggestions don't depend on hashmap order
+        .min()
+        .map(|(_, c)| c.to_string())
+}
+
+/// levenshtein distance, counting an adjacent transposition as a single edit
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+
+    // three rows of the usual dynamic programming table
+    let mut prev2 = vec![0; b.len() + 1];
+    let mut prev = (0..=b.len()).collect::<Vec<_>>();
+    let mut cur = vec![0; b.len() + 1];
+
+    for i in 1..=a.len() {
+        cur[0] = i;
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            cur[j] = (prev[j] + 1).min(cur[j - 1] + 1).min(prev[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                cur[j] = cur[j].min(prev2[j - 2] + 1);
+            }
+        }
+        std::mem::swap(&mut prev2, &mut prev);
+        std::mem::swap(&mut prev, &mut cur);
+    }
+
+    prev[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distance() {
+        assert_eq!(edit_distance("hover", "hover"), 0);
+        assert_eq!(edit_distance("hovr", "hover"), 1);
+        assert_eq!(edit_distance("hvoer", "hover"), 1);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("fcous", "focus"), 1);
+    }
+
+    #[test]
+    fn suggestions() {
+        let candidates = ["hover", "focus", "first", "last"];
+        assert_eq!(suggest("hovr", candidates), Some("hover".to_string()));
+        assert_eq!(suggest("fisrt", candidates), Some("first".to_string()));
+        assert_eq!(suggest("meow", candidates), None);
+        assert_eq!(suggest("x", candidates), None);
+    }
+
+    #[test]
+    fn display() {
+        let err = ZephyrError::new("c[red]hovr", 6..10, ZephyrErrorKind::UnknownModifier)
+            .with_suggestion(Some("hover".to_string()));
+        assert_eq!(
+            err.to_string(),
+            "unknown modifier `hovr` in `c[red]hovr` at 6..10, did you mean `hover`?"
+        );
+    }
+}
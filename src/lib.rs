@@ -1,13 +1,28 @@
 use std::collections::HashMap;
 
-use crate::{defaults::*, parse::*};
+use crate::{
+    defaults::*, media_queries::group_rules, modifiers::is_marker, parse::*, theme::variables_block,
+};
 
+pub use crate::{
+    ast::{ParsedClass, ParsedModifiers, Spanned, Value},
+    class::ValueType,
+    error::{ZephyrError, ZephyrErrorKind},
+    media_queries::{Breakpoint, Length, LengthUnit},
+    special::SpecialTemplate,
+    theme::Theme,
+};
+
+mod ast;
 mod class;
 mod consts;
 mod defaults;
+mod error;
 mod media_queries;
 mod modifiers;
 mod parse;
+mod special;
+mod theme;
 
 #[cfg(test)]
 mod tests;
@@ -19,6 +34,12 @@ pub mod inventory;
 #[cfg(feature = "scraping")]
 pub mod scraping;
 
+#[cfg(feature = "tokens")]
+pub mod tokens;
+
+#[cfg(feature = "config")]
+pub mod config;
+
 /// used to generate css out of classes
 ///
 /// contains shorthands and replacements that can be modified
@@ -29,6 +50,10 @@ pub struct Zephyr {
     pub declarations: HashMap<String, String>,
     /// special declarations. Fn(Value) -> declarations
     pub specials: HashMap<String, SpecialDeclaration>,
+    /// special declarations written as templates, which are checked before `specials`
+    ///
+    /// unlike `specials`, they can be written back to a config file
+    pub special_templates: HashMap<String, SpecialTemplate>,
 
     /// list of property short-hands
     pub properties: HashMap<String, String>,
@@ -42,31 +67,111 @@ pub struct Zephyr {
     ///
     /// property -> [(short, expanded)]
     pub context_aware_values: HashMap<String, HashMap<String, String>>,
+    /// breakpoints for the responsive modifiers
+    ///
+    /// `<bp` and `@bp` use the next wider breakpoint, so the order in the list doesn't matter
+    pub breakpoints: Vec<Breakpoint>,
+    /// modifiers that turn into media conditions, other than the responsive ones
+    ///
+    /// the conditions are either media features in parenthesis, like `(prefers-color-scheme:dark)`,
+    /// or media types, like `print`
+    pub media_features: HashMap<String, String>,
+    /// named feature queries, used by `supports-name` modifiers
+    ///
+    /// name -> condition for `@supports`, eg: `grid` -> `(display:grid)`
+    pub supports: HashMap<String, String>,
+    /// design token scales, and the properties that use them
+    pub theme: Theme,
+    /// added to the name of every variable that zephyr reads or defines, eg: `zp-`
+    ///
+    /// with `zp-`, `c(primary)` results in `var(--zp-primary)`
+    pub variable_prefix: String,
 
     pub pretty_print: bool,
+    /// makes unknown pseudo-classes and pseudo-elements errors
+    ///
+    /// otherwise they are used as they are, with a warning, so new ones work before zephyr knows about them
+    pub strict: bool,
 }
 
-/// value -> declarations
-pub type SpecialDeclaration = Box<dyn Fn(&str) -> String>;
+/// the result of [`Zephyr::generate_classes_report`]
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct GenerationReport<'a> {
+    /// the generated css
+    pub css: String,
+    /// classes that were generated, in order and without duplicates
+    pub classes: Vec<&'a str>,
+    /// classes that failed to parse or generate
+    pub errors: Vec<ZephyrError>,
+    /// classes that were skipped because they had already appeared.
+    /// a class appears here once per repetition
+    pub duplicates: Vec<&'a str>,
+}
 
-#[derive(PartialEq, Debug)]
-pub enum ZephyrError {
-    /// the provided rule has invalid braces (single braces, or in incorrect order `..}...{..`)
-    InvalidBraces,
-    /// the provided rule isn't a no-variable rule, but no variables were provided
-    ValueMissing,
+impl GenerationReport<'_> {
+    /// whether any class failed
+    pub fn has_errors(&self) -> bool {
+        !self.errors.is_empty()
+    }
 }
 
+/// value -> declarations
+///
+/// can be built from a [`SpecialTemplate`]
+pub type SpecialDeclaration = Box<dyn Fn(&str) -> String>;
+
 impl Zephyr {
     /// generates css rules for all the of the classes that parse correctly
     pub fn generate_classes<'a>(&self, classes: impl IntoIterator<Item = &'a str>) -> String {
-        // TODO we could return (css, seen_classes)
-        let mut seen_classes = vec![];
+        self.generate_classes_report(classes).css
+    }
+
+    /// generates a `:root` block that defines a variable for every token in [`Zephyr::theme`],
+    /// like `--spacing-4:1rem`, followed by the dark mode overrides if there are any
+    ///
+    /// classes can then use the tokens with the variable syntax, like `c(color-brand-500)`,
+    /// or through [`Theme::use_variables`]
+    pub fn generate_theme_variables(&self) -> String {
+        let pretty = self.pretty_print;
+        let prefix = &self.variable_prefix;
+        let mut css = variables_block(":root", &self.theme.scales, prefix, pretty, 0);
+
+        match &self.theme.dark_selector {
+            Some(selector) => css.push_str(&variables_block(
+                selector,
+                &self.theme.dark,
+                prefix,
+                pretty,
+                0,
+            )),
+            None => {
+                let block = variables_block(":root", &self.theme.dark, prefix, pretty, 1);
+                if !block.is_empty() {
+                    let space = space(pretty);
+                    let nl = nl(pretty);
+                    css.push_str(&format!(
+                        "@media{space}(prefers-color-scheme:dark){space}{{{nl}{block}}}{nl}"
+                    ));
+                }
+            }
+        }
+
+        css
+    }
+
+    /// generates css rules for all the of the classes that parse correctly,
+    /// and reports which classes were generated, which failed, and which were duplicated
+    pub fn generate_classes_report<'a>(
+        &self,
+        classes: impl IntoIterator<Item = &'a str>,
+    ) -> GenerationReport<'a> {
+        let mut report = GenerationReport::default();
 
         let span = tracing::trace_span!("generating classes");
         let _enter = span.enter();
 
-        let classes = classes
+        let mut seen_classes = vec![];
+        let rules = classes
             .into_iter()
             // get a list with one class per element
             .flat_map(|s| s.split_ascii_whitespace())
@@ -77,37 +182,61 @@ impl Zephyr {
             // but i don't really like that
             .filter(|s| {
                 if seen_classes.contains(s) {
+                    report.duplicates.push(*s);
                     false
                 } else {
                     seen_classes.push(s);
                     true
                 }
             })
-            // we ignore errors
-            // TODO change this to call parse_class directly
-            // TODO then group by media query
-            .flat_map(|c| match self.generate_class(c) {
-                Ok(v) => Some(v),
-                Err(err) => {
-                    // trace error
-                    tracing::error!("error generating {c}: {err:?}");
-                    None
+            .flat_map(|c| {
+                // `group` and `peer` only mark elements for other classes' modifiers,
+                // so they are valid but don't generate any rule
+                if is_marker(c) {
+                    report.classes.push(c);
+                    return None;
+                }
+                match parse_class(c).and_then(|class| class.rule(self)) {
+                    Ok(v) => {
+                        report.classes.push(c);
+                        Some(v)
+                    }
+                    Err(err) => {
+                        // trace error
+                        tracing::error!("error generating {c}: {err}");
+                        report.errors.push(err);
+                        None
+                    }
                 }
             })
             .collect::<Vec<_>>();
 
-        let len = classes.len();
+        let len = rules.len();
         tracing::trace!("finished generating {len} classes");
 
-        classes.join(nl(self.pretty_print))
+        report.css = group_rules(rules, self.pretty_print);
+        report
     }
 
     /// this one returns an error if parsing or generating fails
+    ///
+    /// markers like `group` or `peer/card` result in an empty string
     pub fn generate_class(&self, class: &str) -> Result<String, ZephyrError> {
+        if is_marker(class) {
+            return Ok(String::new());
+        }
         let c = parse_class(class)?;
         c.generate_with_media_query(self)
     }
 
+    /// parses a class without generating any css
+    ///
+    /// meant for tooling that needs to understand classes the same way the generator does
+    pub fn parse<'a>(&self, class: &'a str) -> Result<ParsedClass<'a>, ZephyrError> {
+        let c = parse_class(class)?;
+        Ok(ParsedClass::new(&c, self))
+    }
+
     /// builds a `Zephyr` with the default ruleset
     pub fn new() -> Self {
         Self {
@@ -117,8 +246,15 @@ impl Zephyr {
             modifiers: default_modifiers(),
             pseudos: default_pseudos(),
             specials: default_specials(),
+            special_templates: HashMap::new(),
             context_aware_values: default_context_aware_values(),
+            breakpoints: default_breakpoints(),
+            media_features: default_media_features(),
+            supports: default_supports(),
+            theme: default_theme(),
+            variable_prefix: String::new(),
             pretty_print: false,
+            strict: false,
         }
     }
 
@@ -135,13 +271,25 @@ impl Zephyr {
 }
 
 pub(crate) fn space(b: bool) -> &'static str {
-    b.then_some(" ").unwrap_or_default()
+    if b {
+        " "
+    } else {
+        ""
+    }
 }
 
 pub(crate) fn indent(b: bool, level: usize) -> String {
-    b.then_some("    ".repeat(level)).unwrap_or_default()
+    if b {
+        "    ".repeat(level)
+    } else {
+        String::new()
+    }
 }
 
 pub(crate) fn nl(b: bool) -> &'static str {
-    b.then_some("\n").unwrap_or_default()
+    if b {
+        "\n"
+    } else {
+        ""
+    }
 }
@@ -1,18 +1,54 @@
-use crate::{nl, space};
+use std::{fmt, str::FromStr};
+
+use crate::{indent, nl, space, Zephyr};
 
 #[derive(PartialEq, Debug)]
-pub(crate) struct Responsive {
-    breakpoint: Breakpoint,
-    range: Range,
+pub(crate) enum Responsive<'z> {
+    /// a named breakpoint, eg: `md`, `<lg` or `@xl`
+    Breakpoint {
+        breakpoint: &'z Breakpoint,
+        /// the smallest breakpoint that is wider than `breakpoint`
+        next: Option<&'z Breakpoint>,
+        range: Range,
+    },
+    /// a one-off threshold, eg: `min-[900px]` or `h-max-[40em]`
+    Arbitrary { feature: Feature, length: Length },
 }
 
-#[derive(PartialEq, Debug)]
-pub(crate) enum Breakpoint {
-    Sm,
-    Md,
-    Lg,
-    Xl,
-    Xxl,
+/// the media features that arbitrary responsive modifiers can use
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub(crate) enum Feature {
+    /// `min-[...]`
+    MinWidth,
+    /// `max-[...]`
+    MaxWidth,
+    /// `h-min-[...]`
+    MinHeight,
+    /// `h-max-[...]`
+    MaxHeight,
+}
+
+/// a named viewport width, used by responsive modifiers
+///
+/// eg: `md` in `m[1rem]md`
+#[derive(PartialEq, Debug, Clone)]
+pub struct Breakpoint {
+    pub name: String,
+    pub width: Length,
+}
+
+/// a css length, like `768px` or `48em`
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct Length {
+    pub value: f64,
+    pub unit: LengthUnit,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum LengthUnit {
+    Px,
+    Em,
+    Rem,
 }
 
 #[derive(PartialEq, Debug, Default)]
@@ -23,169 +59,717 @@ pub(crate) enum Range {
     Exact,
 }
 
-impl Responsive {
-    pub fn queries(&self) -> Vec<String> {
-        match self.range {
-            Range::Gte => vec![format!("min-width:{}px", self.breakpoint.width())],
-            Range::Lt => vec![format!("max-width:{}.9px", self.breakpoint.width() - 1)],
-            Range::Exact => {
-                if let Some(n) = self.breakpoint.next() {
-                    vec![
-                        format!("min-width:{}px", self.breakpoint.width()),
-                        format!("max-width:{}.9px", n.width() - 1),
-                    ]
-                } else {
-                    vec![format!("min-width:{}px", self.breakpoint.width())]
+impl Responsive<'_> {
+    /// the bounds this modifier puts on the viewport
+    fn constraints(&self) -> Vec<(Feature, Length)> {
+        match self {
+            Responsive::Breakpoint {
+                breakpoint,
+                next,
+                range,
+            } => {
+                let min = (Feature::MinWidth, breakpoint.width);
+                match range {
+                    Range::Gte => vec![min],
+                    Range::Lt => vec![(Feature::MaxWidth, breakpoint.width.just_below())],
+                    Range::Exact => {
+                        if let Some(n) = next {
+                            vec![min, (Feature::MaxWidth, n.width.just_below())]
+                        } else {
+                            vec![min]
+                        }
+                    }
                 }
             }
+            Responsive::Arbitrary { feature, length } => vec![(*feature, *length)],
         }
     }
 
-    pub fn from_str(s: &str) -> Option<Self> {
+    pub fn from_str<'z>(s: &str, z: &'z Zephyr) -> Option<Responsive<'z>> {
         if s.is_empty() {
             return None;
         }
 
-        if let Some(p) = s.strip_prefix('<') {
-            Some(Responsive {
-                breakpoint: Breakpoint::from_str(p)?,
-                range: Range::Lt,
-            })
+        if let Some(r) = Self::arbitrary(s) {
+            return Some(r);
+        }
+
+        let (name, range) = if let Some(p) = s.strip_prefix('<') {
+            (p, Range::Lt)
         } else if let Some(p) = s.strip_prefix('@') {
-            Some(Responsive {
-                breakpoint: Breakpoint::from_str(p)?,
-                range: Range::Exact,
-            })
+            (p, Range::Exact)
         } else {
-            Some(Responsive {
-                breakpoint: Breakpoint::from_str(s)?,
-                range: Range::Gte,
-            })
+            (s, Range::Gte)
+        };
+
+        let breakpoint = z.breakpoints.iter().find(|b| b.name == name)?;
+        let next = z
+            .breakpoints
+            .iter()
+            .filter(|b| b.width.order() > breakpoint.width.order())
+            .min_by_key(|b| b.width.order());
+
+        Some(Responsive::Breakpoint {
+            breakpoint,
+            next,
+            range,
+        })
+    }
+
+    /// parses a container query modifier: `@c-md`, `cq-min-[400px]` or `cq-sidebar-md`
+    ///
+    /// returns the name of the container, if any, and the size condition,
+    /// which can be anything that is a valid responsive modifier
+    pub fn container<'a, 'z>(
+        s: &'a str,
+        z: &'z Zephyr,
+    ) -> Option<(Option<&'a str>, Responsive<'z>)> {
+        if let Some(rest) = s.strip_prefix("@c-") {
+            return Some((None, Responsive::from_str(rest, z)?));
+        }
+
+        let rest = s.strip_prefix("cq-")?;
+        if let Some(r) = Responsive::from_str(rest, z) {
+            return Some((None, r));
         }
+        // names can contain hyphens too, so we try every possible split
+        rest.match_indices('-').find_map(|(i, _)| {
+            let name = &rest[..i];
+            let r = Responsive::from_str(&rest[i + 1..], z)?;
+            (!name.is_empty()).then_some((Some(name), r))
+        })
+    }
+
+    /// parses `min-[900px]`, `max-[40em]`, `h-min-[600px]` and `h-max-[600px]`
+    fn arbitrary(s: &str) -> Option<Responsive<'static>> {
+        let (feature, rest) = [
+            ("min-[", Feature::MinWidth),
+            ("max-[", Feature::MaxWidth),
+            ("h-min-[", Feature::MinHeight),
+            ("h-max-[", Feature::MaxHeight),
+        ]
+        .into_iter()
+        .find_map(|(prefix, f)| Some((f, s.strip_prefix(prefix)?)))?;
+
+        let length = rest.strip_suffix(']')?.parse().ok()?;
+        Some(Responsive::Arbitrary { feature, length })
     }
 }
 
-impl Breakpoint {
-    pub fn from_str(s: &str) -> Option<Self> {
-        match s {
-            "sm" => Some(Self::Sm),
-            "md" => Some(Self::Md),
-            "lg" => Some(Self::Lg),
-            "xl" => Some(Self::Xl),
-            "xxl" => Some(Self::Xxl),
-            _ => None,
+/// the combination of all the responsive modifiers of a class
+#[derive(PartialEq, Debug, Default, Clone, Copy)]
+pub(crate) struct ViewportRange {
+    min_width: Option<Length>,
+    max_width: Option<Length>,
+    min_height: Option<Length>,
+    max_height: Option<Length>,
+}
+
+impl ViewportRange {
+    /// narrows the range down to what `r` allows.
+    /// returns `false` if no viewport fits in the resulting range
+    pub(crate) fn add(&mut self, r: &Responsive) -> bool {
+        for (feature, length) in r.constraints() {
+            let (bound, keep_bigger) = match feature {
+                Feature::MinWidth => (&mut self.min_width, true),
+                Feature::MaxWidth => (&mut self.max_width, false),
+                Feature::MinHeight => (&mut self.min_height, true),
+                Feature::MaxHeight => (&mut self.max_height, false),
+            };
+            let replace = match bound {
+                Some(b) if keep_bigger => length.order() > b.order(),
+                Some(b) => length.order() < b.order(),
+                None => true,
+            };
+            if replace {
+                *bound = Some(length);
+            }
         }
+
+        let fits = |min: Option<Length>, max: Option<Length>| match (min, max) {
+            (Some(min), Some(max)) => min.order() <= max.order(),
+            _ => true,
+        };
+        fits(self.min_width, self.max_width) && fits(self.min_height, self.max_height)
     }
 
-    fn width(&self) -> u16 {
-        match self {
-            Breakpoint::Sm => 640,
-            Breakpoint::Md => 768,
-            Breakpoint::Lg => 1024,
-            Breakpoint::Xl => 1280,
-            Breakpoint::Xxl => 1536,
+    pub(crate) fn queries(&self) -> Vec<String> {
+        [
+            (Feature::MinWidth, self.min_width),
+            (Feature::MaxWidth, self.max_width),
+            (Feature::MinHeight, self.min_height),
+            (Feature::MaxHeight, self.max_height),
+        ]
+        .into_iter()
+        .filter_map(|(f, l)| Some(format!("{}:{}", f.name(), l?)))
+        .collect()
+    }
+
+    /// mobile-first order: `min-width` queries go from small to big, and are followed by
+    /// `max-width` queries from big to small, so that the narrowest query always wins.
+    /// ranges with both go next, and queries on only the height go last, in the same order
+    fn order(&self) -> (u8, i64, u8, i64) {
+        fn order(min: Option<Length>, max: Option<Length>) -> (u8, i64) {
+            match (min, max) {
+                (None, None) => (0, 0),
+                (Some(min), None) => (1, min.order()),
+                (None, Some(max)) => (2, -max.order()),
+                (Some(min), Some(_)) => (3, min.order()),
+            }
+        }
+
+        let (w, w_value) = order(self.min_width, self.max_width);
+        let (h, h_value) = order(self.min_height, self.max_height);
+        match (w, h) {
+            (0, 0) => (0, 0, 0, 0),
+            (0, h) => (4, 0, h, h_value),
+            (w, h) => (w, w_value, h, h_value),
         }
     }
+}
 
-    const fn next(&self) -> Option<Self> {
+impl Feature {
+    fn name(&self) -> &'static str {
         match self {
-            Breakpoint::Sm => Some(Breakpoint::Md),
-            Breakpoint::Md => Some(Breakpoint::Lg),
-            Breakpoint::Lg => Some(Breakpoint::Xl),
-            Breakpoint::Xl => Some(Breakpoint::Xxl),
-            Breakpoint::Xxl => None,
+            Feature::MinWidth => "min-width",
+            Feature::MaxWidth => "max-width",
+            Feature::MinHeight => "min-height",
+            Feature::MaxHeight => "max-height",
         }
     }
 }
 
-#[derive(PartialEq, Debug)]
-pub enum ReducedMotion {
-    MotionReduce,
-    MotionSafe,
+impl Breakpoint {
+    pub fn new(name: impl Into<String>, width: Length) -> Self {
+        Self {
+            name: name.into(),
+            width,
+        }
+    }
 }
 
-impl ReducedMotion {
-    pub fn queries(&self) -> &[&str] {
-        match self {
-            Self::MotionReduce => &["prefers-reduced-motion:reduce"],
-            Self::MotionSafe => &["prefers-reduced-motion:no-preference"],
+impl Length {
+    pub const fn px(value: f64) -> Self {
+        Self {
+            value,
+            unit: LengthUnit::Px,
+        }
+    }
+
+    pub const fn em(value: f64) -> Self {
+        Self {
+            value,
+            unit: LengthUnit::Em,
         }
     }
-    pub fn from_str(s: &str) -> Option<Self> {
-        match s {
-            "motion-reduce" => Some(ReducedMotion::MotionReduce),
-            "motion-safe" => Some(ReducedMotion::MotionSafe),
-            _ => None,
+
+    pub const fn rem(value: f64) -> Self {
+        Self {
+            value,
+            unit: LengthUnit::Rem,
+        }
+    }
+
+    /// the length in pixels, assuming the default font size of 16px
+    fn to_px(self) -> f64 {
+        match self.unit {
+            LengthUnit::Px => self.value,
+            LengthUnit::Em | LengthUnit::Rem => self.value * 16.0,
         }
     }
+
+    /// integer key used to sort lengths, in hundredths of a pixel
+    pub(crate) fn order(&self) -> i64 {
+        (self.to_px() * 100.0).round() as i64
+    }
+
+    /// the length 0.1px below this one, in the same unit. used for `max-width` queries,
+    /// so they don't overlap with the `min-width` query of the same length
+    pub(crate) fn just_below(self) -> Self {
+        let step = match self.unit {
+            LengthUnit::Px => 0.1,
+            LengthUnit::Em | LengthUnit::Rem => 0.1 / 16.0,
+        };
+        Self {
+            value: self.value - step,
+            unit: self.unit,
+        }
+    }
+}
+
+impl fmt::Display for Length {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // rounding gets rid of floating point noise, like `1023.9000000000001`
+        let value = (self.value * 100_000.0).round() / 100_000.0;
+        let unit = match self.unit {
+            LengthUnit::Px => "px",
+            LengthUnit::Em => "em",
+            LengthUnit::Rem => "rem",
+        };
+        write!(f, "{value}{unit}")
+    }
 }
 
-pub(crate) fn wrap_in_query(css: String, queries: &[String], pretty_print: bool) -> String {
-    if queries.is_empty() {
-        return css;
+impl FromStr for Length {
+    type Err = ();
+
+    /// parses lengths like `640px`, `40em` or `2.5rem`. negative lengths aren't valid widths
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (value, unit) = if let Some(v) = s.strip_suffix("px") {
+            (v, LengthUnit::Px)
+        } else if let Some(v) = s.strip_suffix("rem") {
+            (v, LengthUnit::Rem)
+        } else if let Some(v) = s.strip_suffix("em") {
+            (v, LengthUnit::Em)
+        } else {
+            return Err(());
+        };
+        let value = value.parse::<f64>().map_err(|_| ())?;
+        (value.is_finite() && value >= 0.0)
+            .then_some(Self { value, unit })
+            .ok_or(())
     }
-    let query = queries
+}
+
+/// decides the order of the `@media` blocks in the output
+///
+/// rules without a responsive modifier come first, followed by the responsive ones
+/// in [`ViewportRange::order`]. for the same range, rules without media features go first,
+/// and the rest are sorted by their media features. the queries themselves break any remaining ties.
+///
+/// inside of each `@media` block, rules without `@supports` go first, and inside of those,
+/// rules without container queries go first
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub(crate) struct QueryOrder {
+    responsive: (u8, i64, u8, i64),
+    features: Vec<String>,
+    queries: Vec<String>,
+    supports: Vec<String>,
+    container: Option<ContainerQuery>,
+}
+
+impl QueryOrder {
+    /// `features` must be sorted, and `queries` must contain them, along with the responsive ones.
+    /// `supports` must be sorted
+    pub(crate) fn new(
+        responsive: &ViewportRange,
+        features: Vec<String>,
+        queries: Vec<String>,
+        supports: Vec<String>,
+        container: Option<ContainerQuery>,
+    ) -> Self {
+        Self {
+            responsive: responsive.order(),
+            features,
+            queries,
+            supports,
+            container,
+        }
+    }
+
+    /// the preludes of the at-rules the rule has to be wrapped in, from the outermost to the innermost
+    fn preludes(&self, pretty_print: bool) -> Vec<String> {
+        let space = space(pretty_print);
+        let mut preludes = vec![];
+
+        if !self.queries.is_empty() {
+            // media types need a space before them, but parenthesis don't
+            let before = if is_media_type(&self.queries[0]) {
+                " "
+            } else {
+                space
+            };
+            let query = join_conditions(&self.queries, pretty_print);
+            preludes.push(format!("@media{before}{query}"));
+        }
+        if !self.supports.is_empty() {
+            // `and` always needs spaces around it in `@supports`
+            let query = self.supports.join(" and ");
+            preludes.push(format!("@supports{space}{query}"));
+        }
+        if let Some(c) = &self.container {
+            let query = join_conditions(&c.queries, pretty_print);
+            // the name needs a space after it, or it would be parsed as a function
+            let name = if c.name.is_empty() {
+                space.to_string()
+            } else {
+                format!(" {} ", c.name)
+            };
+            preludes.push(format!("@container{name}{query}"));
+        }
+
+        preludes
+    }
+}
+
+/// an `@container` query
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub(crate) struct ContainerQuery {
+    /// empty for the nearest container
+    name: String,
+    range: (u8, i64, u8, i64),
+    queries: Vec<String>,
+}
+
+impl ContainerQuery {
+    pub(crate) fn new(name: Option<&str>, range: &ViewportRange) -> Self {
+        Self {
+            name: name.unwrap_or_default().to_string(),
+            range: range.order(),
+            queries: range
+                .queries()
+                .into_iter()
+                .map(|q| format!("({q})"))
+                .collect(),
+        }
+    }
+}
+
+/// parses a feature query modifier: `supports-[display:grid]`, or one from [`Zephyr::supports`]
+///
+/// returns the condition for `@supports`
+pub(crate) fn supports(s: &str, z: &Zephyr) -> Option<String> {
+    let rest = s.strip_prefix("supports-")?;
+    if let Some(condition) = z.supports.get(rest) {
+        return Some(condition.clone());
+    }
+
+    let condition = rest.strip_prefix('[')?.strip_suffix(']')?.replace('_', " ");
+    if condition.is_empty() {
+        return None;
+    }
+    // things like `not (display:grid)` or `selector(:has(a))` are already complete conditions
+    let is_complete = condition.starts_with('(')
+        || condition.starts_with("not ")
+        || condition.split_once('(').is_some_and(|(f, _)| {
+            !f.is_empty() && f.chars().all(|c| c.is_ascii_alphabetic() || c == '-')
+        });
+    if is_complete {
+        Some(condition)
+    } else {
+        Some(format!("({condition})"))
+    }
+}
+
+/// whether a media condition is a media type, like `print`, instead of a media feature like `(hover:hover)`
+///
+/// media types have to go before any media features
+pub(crate) fn is_media_type(condition: &str) -> bool {
+    !condition.starts_with('(')
+}
+
+/// a generated css rule, and the at-rules it has to be wrapped in
+pub(crate) struct Rule {
+    pub css: String,
+    pub order: QueryOrder,
+}
+
+/// joins the rules, grouping together the ones that share the same at-rules
+///
+/// rules with no media queries go first, and then a single `@media` block for each list of queries,
+/// sorted by [`QueryOrder`]. inside each block, rules with the same `@supports` are grouped in
+/// a single `@supports` block, and the same goes for `@container` inside of those.
+/// otherwise, rules keep their original order
+pub(crate) fn group_rules(mut rules: Vec<Rule>, pretty_print: bool) -> String {
+    // sort is stable, so rules with the same queries keep their order
+    rules.sort_by(|a, b| a.order.cmp(&b.order));
+
+    let rules = rules
         .iter()
-        .map(|s| format!("({s})"))
-        .collect::<Vec<String>>()
-        .join(if pretty_print { " and " } else { "and" });
+        .map(|r| (r.order.preludes(pretty_print), r.css.as_str()))
+        .collect::<Vec<_>>();
+    group_level(&rules, 0, pretty_print)
+}
+
+/// groups the rules by their at-rule at `level`, and wraps each group in it
+fn group_level(rules: &[(Vec<String>, &str)], level: usize, pretty_print: bool) -> String {
     let space = space(pretty_print);
     let nl = nl(pretty_print);
-    format!("@media{space}{query}{space}{{{nl}{css}}}{nl}")
+    let indent = indent(pretty_print, level);
+
+    rules
+        .chunk_by(|a, b| a.0.get(level) == b.0.get(level))
+        .map(|group| match group[0].0.get(level) {
+            Some(prelude) => {
+                let css = group_level(group, level + 1, pretty_print);
+                format!("{indent}{prelude}{space}{{{nl}{css}{indent}}}{nl}")
+            }
+            None => group.iter().map(|r| r.1).collect::<Vec<_>>().join(nl),
+        })
+        .collect::<Vec<_>>()
+        .join(nl)
+}
+
+/// joins conditions with `and`
+fn join_conditions(conditions: &[String], pretty_print: bool) -> String {
+    let space = space(pretty_print);
+    let mut query = String::new();
+    for (i, q) in conditions.iter().enumerate() {
+        if i > 0 {
+            // media types need spaces around them, but parenthesis don't
+            if is_media_type(q) || !query.ends_with(')') {
+                query.push_str(" and ");
+            } else {
+                query.push_str(space);
+                query.push_str("and");
+                query.push_str(space);
+            }
+        }
+        query.push_str(q);
+    }
+    query
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn queries(m: &str, z: &Zephyr) -> Vec<String> {
+        let mut range = ViewportRange::default();
+        range.add(&Responsive::from_str(m, z).unwrap());
+        range.queries()
+    }
+
     #[test]
     fn parse_responsive() {
-        let r = Responsive::from_str("<lg");
+        let z = Zephyr::new();
+        let bp = |i: usize| &z.breakpoints[i];
+
+        let r = Responsive::from_str("<lg", &z);
         assert_eq!(
             r,
-            Some(Responsive {
-                breakpoint: Breakpoint::Lg,
+            Some(Responsive::Breakpoint {
+                breakpoint: bp(2),
+                next: Some(bp(3)),
                 range: Range::Lt
             })
         );
 
-        let r = Responsive::from_str("@xl");
+        let r = Responsive::from_str("@xl", &z);
         assert_eq!(
             r,
-            Some(Responsive {
-                breakpoint: Breakpoint::Xl,
+            Some(Responsive::Breakpoint {
+                breakpoint: bp(3),
+                next: Some(bp(4)),
                 range: Range::Exact
             })
         );
 
-        let r = Responsive::from_str("@sm");
+        let r = Responsive::from_str("@sm", &z);
         assert_eq!(
             r,
-            Some(Responsive {
-                breakpoint: Breakpoint::Sm,
+            Some(Responsive::Breakpoint {
+                breakpoint: bp(0),
+                next: Some(bp(1)),
                 range: Range::Exact
             })
         );
 
-        let r = Responsive::from_str("xxl");
+        let r = Responsive::from_str("xxl", &z);
         assert_eq!(
             r,
-            Some(Responsive {
-                breakpoint: Breakpoint::Xxl,
+            Some(Responsive::Breakpoint {
+                breakpoint: bp(4),
+                next: None,
                 range: Range::Gte
             })
         );
+
+        assert_eq!(Responsive::from_str("tablet", &z), None);
     }
 
     #[test]
     fn generate_queries() {
-        let r = Responsive::from_str("<lg").unwrap().queries();
+        let z = Zephyr::new();
+
+        let r = queries("<lg", &z);
         assert_eq!(r, &["max-width:1023.9px"]);
 
-        let r = Responsive::from_str("@xl").unwrap().queries();
+        let r = queries("@xl", &z);
         assert_eq!(r, &["min-width:1280px", "max-width:1535.9px"]);
+    }
+
+    #[test]
+    fn wrap_media_types() {
+        let wrap = |queries: &[&str], pretty_print| {
+            let queries = queries.iter().map(ToString::to_string).collect::<Vec<_>>();
+            let order = QueryOrder::new(&ViewportRange::default(), vec![], queries, vec![], None);
+            let css = "a{}".to_string();
+            group_rules(vec![Rule { css, order }], pretty_print)
+        };
+
+        assert_eq!(wrap(&["print"], false), "@media print{a{}}");
+        assert_eq!(
+            wrap(&["print", "(min-width:640px)", "(hover:hover)"], false),
+            "@media print and (min-width:640px)and(hover:hover){a{}}"
+        );
+        assert_eq!(
+            wrap(&["print", "(min-width:640px)"], true),
+            "@media print and (min-width:640px) {\na{}}\n"
+        );
+    }
+
+    #[test]
+    fn parse_supports() {
+        let z = Zephyr::new();
+
+        assert_eq!(
+            supports("supports-grid", &z).as_deref(),
+            Some("(display:grid)")
+        );
+        assert_eq!(
+            supports("supports-[display:grid]", &z).as_deref(),
+            Some("(display:grid)")
+        );
+        assert_eq!(
+            supports("supports-[not_(display:grid)]", &z).as_deref(),
+            Some("not (display:grid)")
+        );
+        assert_eq!(
+            supports("supports-[selector(:has(a))]", &z).as_deref(),
+            Some("selector(:has(a))")
+        );
+        assert_eq!(
+            supports("supports-[(a:b)_or_(c:d)]", &z).as_deref(),
+            Some("(a:b) or (c:d)")
+        );
+        assert_eq!(supports("supports-[]", &z), None);
+        assert_eq!(supports("supports-nope", &z), None);
+        assert_eq!(supports("grid", &z), None);
+    }
+
+    #[test]
+    fn arbitrary_queries() {
+        let z = Zephyr::new();
 
-        let r = ReducedMotion::MotionReduce.queries();
-        assert_eq!(r, &["prefers-reduced-motion:reduce"]);
+        let r = Responsive::from_str("min-[900px]", &z);
+        assert_eq!(
+            r,
+            Some(Responsive::Arbitrary {
+                feature: Feature::MinWidth,
+                length: Length::px(900.0)
+            })
+        );
+        assert_eq!(queries("min-[900px]", &z), &["min-width:900px"]);
+
+        let r = queries("max-[40em]", &z);
+        assert_eq!(r, &["max-width:40em"]);
+
+        let r = queries("h-min-[600px]", &z);
+        assert_eq!(r, &["min-height:600px"]);
+
+        let r = queries("h-max-[37.5rem]", &z);
+        assert_eq!(r, &["max-height:37.5rem"]);
+
+        assert_eq!(Responsive::from_str("min-[900]", &z), None);
+        assert_eq!(Responsive::from_str("min-[wide]", &z), None);
+        assert_eq!(Responsive::from_str("min-900px", &z), None);
+        assert_eq!(Responsive::from_str("min-[-5px]", &z), None);
+    }
+
+    #[test]
+    fn combine_ranges() {
+        let z = Zephyr::new();
+        let range = |mods: &[&str]| {
+            let mut range = ViewportRange::default();
+            let fits = mods
+                .iter()
+                .all(|m| range.add(&Responsive::from_str(m, &z).unwrap()));
+            fits.then(|| range.queries())
+        };
+
+        assert_eq!(
+            range(&["md", "<xl"]),
+            Some(vec![
+                "min-width:768px".to_string(),
+                "max-width:1279.9px".to_string()
+            ])
+        );
+        // the narrowest bounds win
+        assert_eq!(
+            range(&["sm", "<xl", "md", "max-[1000px]"]),
+            Some(vec![
+                "min-width:768px".to_string(),
+                "max-width:1000px".to_string()
+            ])
+        );
+        assert_eq!(
+            range(&["@md", "h-min-[500px]"]),
+            Some(vec![
+                "min-width:768px".to_string(),
+                "max-width:1023.9px".to_string(),
+                "min-height:500px".to_string()
+            ])
+        );
+        assert_eq!(range(&["lg", "<sm"]), None);
+        assert_eq!(range(&["@md", "@lg"]), None);
+        assert_eq!(range(&["h-min-[50em]", "h-max-[600px]"]), None);
+    }
+
+    #[test]
+    fn parse_containers() {
+        let z = Zephyr::new();
+        let container = |m| {
+            let (name, r) = Responsive::container(m, &z)?;
+            let mut range = ViewportRange::default();
+            range.add(&r);
+            Some((name, range.queries()))
+        };
+
+        assert_eq!(
+            container("@c-md"),
+            Some((None, vec!["min-width:768px".to_string()]))
+        );
+        assert_eq!(
+            container("cq-min-[400px]"),
+            Some((None, vec!["min-width:400px".to_string()]))
+        );
+        assert_eq!(
+            container("cq-sidebar-md"),
+            Some((Some("sidebar"), vec!["min-width:768px".to_string()]))
+        );
+        assert_eq!(
+            container("cq-main-nav-<lg"),
+            Some((Some("main-nav"), vec!["max-width:1023.9px".to_string()]))
+        );
+        assert_eq!(
+            container("cq-card-max-[20rem]"),
+            Some((Some("card"), vec!["max-width:20rem".to_string()]))
+        );
+        assert_eq!(container("cq-sidebar"), None);
+        assert_eq!(container("cq--md"), None);
+        assert_eq!(container("md"), None);
+    }
+
+    #[test]
+    fn custom_breakpoints() {
+        let mut z = Zephyr::new();
+        z.breakpoints = vec![
+            Breakpoint::new("wide", Length::em(80.0)),
+            Breakpoint::new("tablet", Length::em(48.0)),
+            Breakpoint::new("desktop", Length::em(64.0)),
+        ];
+
+        let r = queries("tablet", &z);
+        assert_eq!(r, &["min-width:48em"]);
+
+        let r = queries("<desktop", &z);
+        assert_eq!(r, &["max-width:63.99375em"]);
+
+        let r = queries("@tablet", &z);
+        assert_eq!(r, &["min-width:48em", "max-width:63.99375em"]);
+
+        assert_eq!(Responsive::from_str("md", &z), None);
+    }
+
+    #[test]
+    fn parse_length() {
+        assert_eq!("640px".parse(), Ok(Length::px(640.0)));
+        assert_eq!("40em".parse(), Ok(Length::em(40.0)));
+        assert_eq!("2.5rem".parse(), Ok(Length::rem(2.5)));
+        assert_eq!("2.5".parse::<Length>(), Err(()));
+        assert_eq!("px".parse::<Length>(), Err(()));
+        assert_eq!("-5px".parse::<Length>(), Err(()));
+        assert_eq!(Length::px(768.0).just_below().to_string(), "767.9px");
     }
 }
@@ -1,32 +1,212 @@
-use crate::media_queries::{ReducedMotion, Responsive};
+use crate::{
+    media_queries::{supports, Responsive},
+    Zephyr,
+};
 
 #[derive(PartialEq, Debug)]
 pub(crate) struct Modifiers<'a> {
     pub all: Vec<&'a str>,
-    pub responsive: Option<Responsive>,
-    pub reduced_motion: Option<ReducedMotion>,
 }
 
 impl<'a> Modifiers<'a> {
     pub(crate) fn new(all: Vec<&'a str>) -> Self {
-        let mut responsive = None;
-        let mut reduced_motion = None;
+        Self { all }
+    }
 
-        for m in &all {
-            responsive = responsive.or_else(|| Responsive::from_str(m));
-            reduced_motion = reduced_motion.or_else(|| ReducedMotion::from_str(m));
-        }
+    /// all the responsive modifiers, along with the modifier they come from
+    pub(crate) fn responsive<'z>(&self, z: &'z Zephyr) -> Vec<(&'a str, Responsive<'z>)> {
+        self.all
+            .iter()
+            .filter_map(|m| Some((*m, Responsive::from_str(m, z)?)))
+            .collect()
+    }
 
-        Self {
-            all,
-            responsive,
-            reduced_motion,
-        }
+    /// all the container query modifiers, along with the modifier they come from and the container name
+    pub(crate) fn containers<'z>(
+        &self,
+        z: &'z Zephyr,
+    ) -> Vec<(&'a str, Option<&'a str>, Responsive<'z>)> {
+        self.all
+            .iter()
+            .filter_map(|m| {
+                let (name, r) = Responsive::container(m, z)?;
+                Some((*m, name, r))
+            })
+            .collect()
+    }
+
+    /// the conditions of all the feature query modifiers, sorted and without duplicates
+    pub(crate) fn supports(&self, z: &Zephyr) -> Vec<String> {
+        let mut conditions = self
+            .all
+            .iter()
+            .filter_map(|m| supports(m, z))
+            .collect::<Vec<_>>();
+        conditions.sort();
+        conditions.dedup();
+        conditions
+    }
+
+    /// the media conditions of all the media feature modifiers, sorted and without duplicates
+    pub(crate) fn media_features(&self, z: &Zephyr) -> Vec<String> {
+        let mut features = self
+            .all
+            .iter()
+            .filter_map(|m| z.media_features.get(*m).cloned())
+            .collect::<Vec<_>>();
+        features.sort();
+        features.dedup();
+        features
     }
 }
 
+/// whether the modifier turns into an at-rule, like `@media`, instead of a pseudo-class
+pub(crate) fn is_at_rule(m: &str, z: &Zephyr) -> bool {
+    Responsive::from_str(m, z).is_some()
+        || z.media_features.contains_key(m)
+        || Responsive::container(m, z).is_some()
+        || supports(m, z).is_some()
+}
+
 impl<'a> From<Vec<&'a str>> for Modifiers<'a> {
     fn from(v: Vec<&'a str>) -> Self {
         Self::new(v)
     }
 }
+
+/// the selector template of an arbitrary selector modifier, eg: `&>p` from `[&>p]`
+///
+/// `&` stands for the class selector, so templates without it aren't valid
+pub(crate) fn selector_template(m: &str) -> Option<&str> {
+    m.strip_prefix('[')?
+        .strip_suffix(']')
+        .filter(|t| t.contains('&'))
+}
+
+/// the element a relational modifier depends on
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy)]
+pub(crate) enum Relation {
+    /// an ancestor marked with `group`
+    Group,
+    /// a previous sibling marked with `peer`
+    Peer,
+}
+
+impl Relation {
+    fn name(self) -> &'static str {
+        match self {
+            Relation::Group => "group",
+            Relation::Peer => "peer",
+        }
+    }
+}
+
+/// a modifier that depends on the state of another element, eg: `group-hover` or `peer-checked/email`
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub(crate) struct Relational<'a> {
+    pub relation: Relation,
+    /// the modifier to apply to the other element
+    pub modifier: &'a str,
+    /// the name after the `/`, for nested groups or multiple peers
+    pub name: Option<&'a str>,
+}
+
+impl<'a> Relational<'a> {
+    pub(crate) fn from_str(m: &'a str) -> Option<Self> {
+        let (relation, rest) = if let Some(rest) = m.strip_prefix("group-") {
+            (Relation::Group, rest)
+        } else {
+            (Relation::Peer, m.strip_prefix("peer-")?)
+        };
+
+        let (modifier, name) = match rest.rsplit_once('/') {
+            Some((modifier, name)) if is_marker_name(name) => (modifier, Some(name)),
+            _ => (rest, None),
+        };
+        if modifier.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            relation,
+            modifier,
+            name,
+        })
+    }
+
+    /// the class of the element this depends on, unescaped, eg: `group/card`
+    pub(crate) fn marker(&self) -> String {
+        match self.name {
+            Some(name) => format!("{}/{name}", self.relation.name()),
+            None => self.relation.name().to_string(),
+        }
+    }
+}
+
+fn is_marker_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+}
+
+/// whether the class only marks an element for relational modifiers, eg: `group` or `peer/email`,
+/// so it doesn't generate anything
+pub(crate) fn is_marker(class: &str) -> bool {
+    ["group", "peer"]
+        .iter()
+        .any(|r| match class.strip_prefix(r) {
+            Some("") => true,
+            Some(rest) => rest.strip_prefix('/').is_some_and(is_marker_name),
+            None => false,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_relational() {
+        assert_eq!(
+            Relational::from_str("group-hover"),
+            Some(Relational {
+                relation: Relation::Group,
+                modifier: "hover",
+                name: None,
+            })
+        );
+        assert_eq!(
+            Relational::from_str("peer-focus-visible/email"),
+            Some(Relational {
+                relation: Relation::Peer,
+                modifier: "focus-visible",
+                name: Some("email"),
+            })
+        );
+        assert_eq!(Relational::from_str("group-"), None);
+        assert_eq!(Relational::from_str("hover"), None);
+        assert_eq!(
+            Relational::from_str("group-hover/card").unwrap().marker(),
+            "group/card"
+        );
+    }
+
+    #[test]
+    fn parse_selector_template() {
+        assert_eq!(selector_template("[&>p]"), Some("&>p"));
+        assert_eq!(selector_template("[.js_&]"), Some(".js_&"));
+        assert_eq!(selector_template("[p]"), None);
+        assert_eq!(selector_template("hover"), None);
+    }
+
+    #[test]
+    fn markers() {
+        assert!(is_marker("group"));
+        assert!(is_marker("peer"));
+        assert!(is_marker("group/card"));
+        assert!(!is_marker("group/"));
+        assert!(!is_marker("groups"));
+        assert!(!is_marker("group-hover"));
+    }
+}
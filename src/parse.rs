@@ -1,114 +1,274 @@
 use crate::{
+    ast::span_of,
     class::{Class, ValueType},
-    ZephyrError,
+    ZephyrError, ZephyrErrorKind,
 };
 
-pub(crate) fn parse_class(original: &str) -> Result<Class, ZephyrError> {
-    // this code is kinda repetitive but idk
+/// a top-level piece of a class, as found by [`tokenize`]
+///
+/// anything that isn't a token is plain text
+#[derive(PartialEq, Debug, Clone, Copy)]
+enum Token {
+    /// a balanced bracket group, which can contain other groups
+    ///
+    /// `start` is the position of the opening bracket, and `end` the position of the closing one
+    Group {
+        open: char,
+        start: usize,
+        end: usize,
+    },
+    /// one of the top-level delimiters: `|`, `$` or `,`
+    Delimiter { c: char, position: usize },
+}
+
+/// splits the class into top-level groups and delimiters
+///
+/// brackets of all kinds (`[]`, `()` and `{}`) must be balanced and properly nested.
+/// delimiters inside of a group are not considered, and neither are brackets inside of quotes
+fn tokenize(s: &str) -> Result<Vec<Token>, ZephyrError> {
+    let mut tokens = vec![];
+    // brackets that are currently open, along with their positions
+    let mut stack: Vec<(char, usize)> = vec![];
+
+    let mut chars = s.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '[' | '(' | '{' => stack.push((c, i)),
+            ']' | ')' | '}' => {
+                let (open, start) = stack.pop().ok_or_else(|| {
+                    ZephyrError::new(s, i..i + 1, ZephyrErrorKind::UnexpectedClosingBracket(c))
+                })?;
+                if closing(open) != c {
+                    return Err(ZephyrError::new(
+                        s,
+                        i..i + 1,
+                        ZephyrErrorKind::MismatchedBracket {
+                            expected: closing(open),
+                            found: c,
+                        },
+                    ));
+                }
+                if stack.is_empty() {
+                    tokens.push(Token::Group {
+                        open,
+                        start,
+                        end: i,
+                    });
+                }
+            }
+            // quotes are only meaningful inside of values, eg: `content[')']`
+            '\'' | '"' if !stack.is_empty() => {
+                chars.find(|(_, q)| *q == c).ok_or_else(|| {
+                    ZephyrError::new(s, i..i + 1, ZephyrErrorKind::UnclosedQuote(c))
+                })?;
+            }
+            '|' | '$' | ',' if stack.is_empty() => tokens.push(Token::Delimiter { c, position: i }),
+            _ => {}
+        }
+    }
+
+    if let Some((bracket, position)) = stack.pop() {
+        return Err(ZephyrError::new(
+            s,
+            position..position + 1,
+            ZephyrErrorKind::UnclosedBracket(bracket),
+        ));
+    }
+
+    Ok(tokens)
+}
+
+fn closing(open: char) -> char {
+    match open {
+        '[' => ']',
+        '(' => ')',
+        '{' => '}',
+        _ => unreachable!("not an opening bracket"),
+    }
+}
+
+fn delimiter(tokens: &[Token], d: char) -> Option<usize> {
+    tokens.iter().find_map(|t| match *t {
+        Token::Delimiter { c, position } if c == d => Some(position),
+        _ => None,
+    })
+}
+
+pub(crate) fn parse_class(original: &str) -> Result<Class<'_>, ZephyrError> {
+    let class = parse_parts(original)?;
+
+    if class.property.is_empty() {
+        return Err(ZephyrError::new(
+            original,
+            span_of(original, class.property),
+            ZephyrErrorKind::EmptyProperty,
+        ));
+    }
+    if let Some(m) = class.modifiers.all.iter().find(|m| m.is_empty()) {
+        return Err(ZephyrError::new(
+            original,
+            span_of(original, m),
+            ZephyrErrorKind::EmptyModifier,
+        ));
+    }
+
+    Ok(class)
+}
+
+/// splits the class into its parts, without checking them
+fn parse_parts(original: &str) -> Result<Class<'_>, ZephyrError> {
+    let (body, important) = strip_important(original);
+    let tokens = tokenize(body).map_err(|mut e| {
+        // the spans have to point into the original class
+        let offset = span_of(original, body).start;
+        e.class = original.to_string();
+        e.span = e.span.start + offset..e.span.end + offset;
+        e
+    })?;
 
-    let (class, pseudo) = if let Some((class, pseudo)) = original.split_once('$') {
-        (class, Some(pseudo))
+    let (class, pseudo) = if let Some(p) = delimiter(&tokens, '$') {
+        (&body[..p], Some(&body[p + 1..]))
     } else {
-        (original, None)
+        (body, None)
     };
+    // the `!` can also go right before the pseudo-element, like `mx[1rem]!$after`
+    let (class, important) = match class.strip_suffix('!') {
+        Some(c) if pseudo.is_some() && !important => (c, true),
+        _ => (class, important),
+    };
+    // we only care about the tokens before the pseudo-element
+    let tokens = tokens
+        .into_iter()
+        .take_while(|t| match *t {
+            Token::Group { start, .. } => start < class.len(),
+            Token::Delimiter { position, .. } => position < class.len(),
+        })
+        .collect::<Vec<_>>();
 
-    if let Some(p) = pos(class, '|') {
-        let mods = if p + 1 == class.len() {
-            vec![]
-        } else {
-            class[p + 1..].split(',').collect()
-        };
-
+    if let Some(p) = delimiter(&tokens, '|') {
         return Ok(Class {
             property: &class[0..p],
             value: None,
-            modifiers: mods.into(),
+            modifiers: split_modifiers(class, p + 1, &tokens).into(),
             pseudo,
             original,
             value_type: ValueType::Normal,
+            important,
+            negative: false,
+            custom_property: false,
         });
     }
 
-    // first try braces, then parenthesis, then square brakcets
-    // i know it's kinda ugly and repetitive, but it works for now
-
-    match (pos(class, '{'), pos(class, '}')) {
-        (Some(start), Some(end)) if start <= end => {
-            let mods = if end + 1 == class.len() {
-                vec![]
-            } else {
-                class[end + 1..].split(',').collect()
-            };
+    let groups = tokens
+        .iter()
+        .filter_map(|t| match *t {
+            Token::Group { open, start, end } => Some((open, start, end)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
 
+    // `set(name)[value]` defines a custom property
+    if let [('(', name_start, name_end), (open, start, end), ..] = groups[..] {
+        if &class[..name_start] == "set" && start == name_end + 1 {
             return Ok(Class {
-                property: &class[0..start],
+                property: &class[name_start + 1..name_end],
                 value: Some(&class[start + 1..end]),
-                modifiers: mods.into(),
+                modifiers: split_modifiers(class, end + 1, &tokens).into(),
                 pseudo,
                 original,
-                value_type: ValueType::Literal,
+                value_type: value_type(open),
+                important,
+                negative: false,
+                custom_property: true,
             });
         }
-        // go to [...] case
-        (None, None) => {}
-        // braces do not form a valid block
-        _ => {
-            return Err(ZephyrError::InvalidBraces);
-        }
-    };
-
-    match (pos(class, '('), pos(class, ')')) {
-        (Some(start), Some(end)) if start <= end => {
-            let mods = if end + 1 == class.len() {
-                vec![]
-            } else {
-                class[end + 1..].split(',').collect()
-            };
-
-            return Ok(Class {
-                property: &class[0..start],
-                value: Some(&class[start + 1..end]),
-                modifiers: mods.into(),
-                pseudo,
-                original,
-                value_type: ValueType::Variable,
-            });
-        }
-        _ => {
-            // do nothing
-        }
     }
 
-    match (pos(class, '['), pos(class, ']')) {
-        (Some(start), Some(end)) if start <= end => {
-            let mods = if end + 1 == class.len() {
-                vec![]
-            } else {
-                class[end + 1..].split(',').collect()
+    // the first group is the value, and its outermost bracket decides the type
+    match groups.first().copied() {
+        Some((open, start, end)) => {
+            let (property, negative) = strip_negative(&class[0..start]);
+            // `--name[value]` defines a custom property too
+            let (property, custom_property) = match property.strip_prefix("--") {
+                Some(name) => (name, true),
+                None => (property, false),
             };
-
             Ok(Class {
-                property: &class[0..start],
+                property,
                 value: Some(&class[start + 1..end]),
-                modifiers: mods.into(),
+                modifiers: split_modifiers(class, end + 1, &tokens).into(),
                 pseudo,
                 original,
-                value_type: ValueType::Normal,
+                value_type: value_type(open),
+                important,
+                negative,
+                custom_property,
             })
         }
-        _ => Ok(Class {
-            property: &class[0..],
+        None => Ok(Class {
+            property: class,
             value: None,
             modifiers: vec![].into(),
             pseudo,
             original,
             value_type: ValueType::Normal,
+            important,
+            negative: false,
+            custom_property: false,
         }),
     }
 }
 
-fn pos(s: &str, c: char) -> Option<usize> {
-    s.find(|v| v == c)
+fn value_type(open: char) -> ValueType {
+    match open {
+        '{' => ValueType::Literal,
+        '(' => ValueType::Variable,
+        _ => ValueType::Normal,
+    }
+}
+
+/// removes the `!` that makes a class important, which can be at the start or at the end.
+/// the one before the pseudo-element is removed by [`parse_parts`]
+fn strip_important(class: &str) -> (&str, bool) {
+    if let Some(c) = class.strip_prefix('!') {
+        (c, true)
+    } else if let Some(c) = class.strip_suffix('!') {
+        (c, true)
+    } else {
+        (class, false)
+    }
+}
+
+/// removes the `-` that negates the value, eg: `-mt[1rem]`
+///
+/// custom properties (`--x`) and vendor prefixes (`-webkit-`) aren't negations
+fn strip_negative(property: &str) -> (&str, bool) {
+    const PREFIXES: [&str; 5] = ["--", "-webkit-", "-moz-", "-ms-", "-o-"];
+    match property.strip_prefix('-') {
+        Some(p) if !PREFIXES.iter().any(|v| property.starts_with(v)) => (p, true),
+        _ => (property, false),
+    }
+}
+
+/// splits `class[start..]` on the top-level commas
+fn split_modifiers<'a>(class: &'a str, start: usize, tokens: &[Token]) -> Vec<&'a str> {
+    if start == class.len() {
+        return vec![];
+    }
+
+    let mut mods = vec![];
+    let mut from = start;
+    for t in tokens {
+        match *t {
+            Token::Delimiter { c: ',', position } if position >= start => {
+                mods.push(&class[from..position]);
+                from = position + 1;
+            }
+            _ => {}
+        }
+    }
+    mods.push(&class[from..]);
+    mods
 }
 
 #[cfg(test)]
@@ -128,6 +288,9 @@ mod tests {
                 pseudo,
                 original: class,
                 value_type: ValueType::Normal,
+                important: false,
+                negative: false,
+                custom_property: false,
             })
         );
     }
@@ -150,6 +313,9 @@ mod tests {
                 pseudo,
                 original: class,
                 value_type,
+                important: false,
+                negative: false,
+                custom_property: false,
             })
         );
     }
@@ -233,9 +399,71 @@ mod tests {
     }
 
     #[test]
-    fn closing_before_opening_means_no_value() {
-        check("a]b[", ("a]b[", None, vec![], None));
-        check("a]b[c]", ("a]b[c]", None, vec![], None));
+    fn parse_nested_values() {
+        check(
+            "grid-template-columns[repeat(3,1fr)]",
+            ("grid-template-columns", Some("repeat(3,1fr)"), vec![], None),
+        );
+        check(
+            "w[calc(100%-2rem)]hover",
+            ("w", Some("calc(100%-2rem)"), vec!["hover"], None),
+        );
+        check(
+            "bg[url('a(b).png')]$after",
+            ("bg", Some("url('a(b).png')"), vec![], Some("after")),
+        );
+        check("content['|$,']", ("content", Some("'|$,'"), vec![], None));
+        // the outermost bracket decides the type
+        check_with_type(
+            "w{calc(100%_-_var(--a))}",
+            (
+                "w",
+                Some("calc(100%_-_var(--a))"),
+                vec![],
+                None,
+                ValueType::Literal,
+            ),
+        );
+        check_with_type(
+            "m(gap[0])",
+            ("m", Some("gap[0]"), vec![], None, ValueType::Variable),
+        );
+    }
+
+    #[test]
+    fn parse_nested_modifiers() {
+        check(
+            "m[0]hover,not(.a,.b)",
+            ("m", Some("0"), vec!["hover", "not(.a,.b)"], None),
+        );
+    }
+
+    fn check_err(class: &str, span: std::ops::Range<usize>, kind: ZephyrErrorKind) {
+        assert_eq!(parse_class(class), Err(ZephyrError::new(class, span, kind)));
+    }
+
+    #[test]
+    fn unbalanced_brackets_are_errors() {
+        check_err("a]b[", 1..2, ZephyrErrorKind::UnexpectedClosingBracket(']'));
+        check_err(
+            "w[calc(100%]",
+            11..12,
+            ZephyrErrorKind::MismatchedBracket {
+                expected: ')',
+                found: ']',
+            },
+        );
+        check_err("w[calc(100%)", 1..2, ZephyrErrorKind::UnclosedBracket('['));
+        check_err("m{1rem", 1..2, ZephyrErrorKind::UnclosedBracket('{'));
+        check_err("content[')]", 8..9, ZephyrErrorKind::UnclosedQuote('\''));
+    }
+
+    #[test]
+    fn empty_parts_are_errors() {
+        check_err("[red]", 0..0, ZephyrErrorKind::EmptyProperty);
+        check_err("|hover", 0..0, ZephyrErrorKind::EmptyProperty);
+        check_err("m[1rem]hover,", 13..13, ZephyrErrorKind::EmptyModifier);
+        check_err("flex|,hover", 5..5, ZephyrErrorKind::EmptyModifier);
     }
 
     #[test]
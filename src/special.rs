@@ -0,0 +1,139 @@
+use std::fmt;
+
+use crate::{class::split_top_level, SpecialDeclaration, Zephyr};
+
+/// a special declaration written as a template, like `left:{0};right:{0};top:{1};bottom:{1}`
+///
+/// `{val}` is replaced by the whole value, and `{0}`, `{1}`, ... by its comma-separated arguments.
+/// missing arguments take the last one that was given, so `inset-xy[1rem]` is the same as
+/// `inset-xy[1rem,1rem]`. commas inside of brackets or quotes don't split arguments
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct SpecialTemplate(String);
+
+impl SpecialTemplate {
+    pub fn new(template: impl Into<String>) -> Self {
+        Self(template.into())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// fills the template with a value
+    pub fn render(&self, val: &str) -> String {
+        let args = split_top_level(val, ',')
+            .into_iter()
+            .map(str::trim)
+            .collect::<Vec<_>>();
+        let mut out = String::with_capacity(self.0.len());
+        let mut rest = self.0.as_str();
+        while let Some(start) = rest.find('{') {
+            out.push_str(&rest[..start]);
+            rest = &rest[start..];
+            let Some(end) = rest.find('}') else {
+                break;
+            };
+            let name = &rest[1..end];
+            if name == "val" {
+                out.push_str(val);
+            } else if let Ok(i) = name.parse::<usize>() {
+                out.push_str(args.get(i).or(args.last()).copied().unwrap_or_default());
+            } else {
+                out.push_str(&rest[..=end]);
+            }
+            rest = &rest[end + 1..];
+        }
+        out.push_str(rest);
+        out
+    }
+}
+
+impl fmt::Display for SpecialTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<&str> for SpecialTemplate {
+    fn from(s: &str) -> Self {
+        Self::new(s)
+    }
+}
+
+impl From<String> for SpecialTemplate {
+    fn from(s: String) -> Self {
+        Self(s)
+    }
+}
+
+impl From<SpecialTemplate> for SpecialDeclaration {
+    fn from(template: SpecialTemplate) -> Self {
+        Box::new(move |val: &str| template.render(val))
+    }
+}
+
+impl Zephyr {
+    /// adds a special declaration from a template, replacing any special with the same name
+    ///
+    /// the template is kept in [`Zephyr::special_templates`], so it can be serialized again
+    pub fn add_special(&mut self, name: impl Into<String>, template: impl Into<SpecialTemplate>) {
+        let name = name.into();
+        self.specials.remove(&name);
+        self.special_templates.insert(name, template.into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_templates() {
+        let t = SpecialTemplate::new("left:{0};right:{0};top:{1};bottom:{1}");
+        assert_eq!(
+            t.render("1rem,2rem"),
+            "left:1rem;right:1rem;top:2rem;bottom:2rem"
+        );
+        assert_eq!(
+            t.render("1rem"),
+            "left:1rem;right:1rem;top:1rem;bottom:1rem"
+        );
+
+        let t = SpecialTemplate::new("background:{val};color:{0};x:{meow}");
+        assert_eq!(
+            t.render("rgb(1,2,3)"),
+            "background:rgb(1,2,3);color:rgb(1,2,3);x:{meow}"
+        );
+        assert_eq!(SpecialTemplate::new("a:{0").render("b"), "a:{0");
+    }
+
+    #[test]
+    fn add_special() {
+        let mut z = Zephyr::new();
+        z.add_special("inset-xy", "left:{0};right:{0};top:{1};bottom:{1}");
+        z.add_special("mx", "margin-inline:{val}");
+        assert_eq!(
+            z.special_templates["inset-xy"].as_str(),
+            "left:{0};right:{0};top:{1};bottom:{1}"
+        );
+        assert!(!z.specials.contains_key("mx"));
+        assert_eq!(
+            z.generate_classes(["mx[0]"]),
+            r#".mx\[0\]{margin-inline:0}"#
+        );
+
+        assert_eq!(
+            z.generate_classes(["inset-xy[0,1rem]", "inset-xy[2px]hover"]),
+            [
+                r#".inset-xy\[0\,1rem\]{left:0;right:0;top:1rem;bottom:1rem}"#,
+                r#".inset-xy\[2px\]hover:hover{left:2px;right:2px;top:2px;bottom:2px}"#,
+            ]
+            .concat()
+        );
+    }
+}
@@ -12,6 +12,9 @@ fn generate_margin_works() {
         pseudo: None,
         original: "m[1rem]",
         value_type: class::ValueType::Normal,
+        important: false,
+        negative: false,
+        custom_property: false,
     };
     let css = class.generate(&z, 0).unwrap();
     assert_eq!(css, r#".m\[1rem\]{margin:1rem}"#);
@@ -23,6 +26,9 @@ fn generate_margin_works() {
         pseudo: None,
         original: "m[1rem]focus",
         value_type: class::ValueType::Normal,
+        important: false,
+        negative: false,
+        custom_property: false,
     };
     let css = class.generate(&z, 0).unwrap();
     assert_eq!(css, r#".m\[1rem\]focus:focus{margin:1rem}"#);
@@ -34,11 +40,14 @@ fn generate_margin_works() {
         pseudo: None,
         original: "m[1rem]focus,hover,odd",
         value_type: class::ValueType::Normal,
+        important: false,
+        negative: false,
+        custom_property: false,
     };
     let css = class.generate(&z, 0).unwrap();
     assert_eq!(
         css,
-        r#".m\[1rem\]focus,hover,odd:focus:hover:nth-child\(odd\){margin:1rem}"#
+        r#".m\[1rem\]focus\,hover\,odd:focus:hover:nth-child(odd){margin:1rem}"#
     );
 }
 
@@ -52,13 +61,13 @@ fn generate_classes_works() {
     let classes = z.generate_classes(["m[3rem]hover,focus$placeholder"]);
     assert_eq!(
         classes,
-        r#".m\[3rem\]hover,focus\$placeholder:hover:focus::placeholder{margin:3rem}"#
+        r#".m\[3rem\]hover\,focus\$placeholder:hover:focus::placeholder{margin:3rem}"#
     );
 
     let classes = z.generate_classes(["flex|hover,focus$placeholder"]);
     assert_eq!(
         classes,
-        r#".flex\|hover,focus\$placeholder:hover:focus::placeholder{display:flex}"#
+        r#".flex\|hover\,focus\$placeholder:hover:focus::placeholder{display:flex}"#
     );
 
     let classes = z.generate_classes(["mr[0.5rem]"]);
@@ -137,7 +146,7 @@ fn generate_with_media_query() {
     let classes = z.generate_classes(["m[1rem]@xl,motion-reduce"]);
     assert_eq!(
         classes,
-        r#"@media(min-width:1280px)and(max-width:1535.9px)and(prefers-reduced-motion:reduce){.m\[1rem\]\@xl,motion-reduce{margin:1rem}}"#
+        r#"@media(min-width:1280px)and(max-width:1535.9px)and(prefers-reduced-motion:reduce){.m\[1rem\]\@xl\,motion-reduce{margin:1rem}}"#
     );
 }
 
@@ -168,3 +177,838 @@ fn generate_context_aware_value() {
     let classes = z.generate_classes(["tt[u]"]);
     assert_eq!(classes, r#".tt\[u\]{text-transform:uppercase}"#);
 }
+
+#[test]
+fn generate_nested_values() {
+    let z = Zephyr::new();
+
+    let classes = z.generate_classes([
+        "w[calc(100%_-_2rem)]",
+        "grid-template-columns[repeat(3,1fr)]",
+    ]);
+    assert_eq!(
+        classes,
+        r#".w\[calc\(100\%_-_2rem\)\]{width:calc(100% - 2rem)}.grid-template-columns\[repeat\(3\,1fr\)\]{grid-template-columns:repeat(3,1fr)}"#
+    );
+}
+
+#[test]
+fn generate_errors() {
+    let z = Zephyr {
+        strict: true,
+        ..Zephyr::new()
+    };
+
+    let err = z.generate_class("c[red]hovr").unwrap_err();
+    assert_eq!(err.kind, ZephyrErrorKind::UnknownModifier);
+    assert_eq!(err.span, 6..10);
+    assert_eq!(err.suggestion.as_deref(), Some("hover"));
+
+    let err = z.generate_class("c[red]hover,evn").unwrap_err();
+    assert_eq!(err.kind, ZephyrErrorKind::UnknownModifier);
+    assert_eq!(err.span, 12..15);
+    assert_eq!(err.suggestion.as_deref(), Some("even"));
+
+    let err = z.generate_class("content['*']$befor").unwrap_err();
+    assert_eq!(err.kind, ZephyrErrorKind::UnknownPseudo);
+    assert_eq!(err.snippet(), "befor");
+    assert_eq!(err.suggestion.as_deref(), Some("before"));
+
+    let err = z.generate_class("flx|hover").unwrap_err();
+    assert_eq!(err.kind, ZephyrErrorKind::UnknownDeclaration);
+    assert_eq!(err.span, 0..3);
+    assert_eq!(err.suggestion.as_deref(), Some("flex"));
+
+    let err = z.generate_class("mtt").unwrap_err();
+    assert_eq!(err.kind, ZephyrErrorKind::UnknownDeclaration);
+    assert_eq!(err.suggestion.as_deref(), Some("mt[...]"));
+
+    let err = z.generate_class("mx").unwrap_err();
+    assert_eq!(err.kind, ZephyrErrorKind::ValueMissing);
+    assert_eq!(err.suggestion, None);
+
+    let err = z.generate_class("w[calc(1px]").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "expected `)`, found `]` in `w[calc(1px]` at 10..11"
+    );
+}
+
+#[test]
+fn generate_unknown_pseudos() {
+    let z = Zephyr::new();
+
+    let classes = z.generate_classes([
+        "c[red]$view-transition-old(root)",
+        "m[0]open",
+        "m[0]closed",
+        "m[0]some-new-state",
+        "c[red]$some-new-element",
+        "m[0][p]",
+    ]);
+    assert_eq!(
+        classes,
+        [
+            r#".c\[red\]\$view-transition-old\(root\)::view-transition-old(root){color:red}"#,
+            r#".m\[0\]open:open{margin:0}"#,
+            r#".m\[0\]closed:closed{margin:0}"#,
+            r#".m\[0\]some-new-state:some-new-state{margin:0}"#,
+            r#".c\[red\]\$some-new-element::some-new-element{color:red}"#,
+        ]
+        .concat()
+    );
+
+    let z = Zephyr {
+        strict: true,
+        ..Zephyr::new()
+    };
+    assert!(z.generate_class("m[0]open").is_ok());
+    let err = z.generate_class("m[0]some-new-state").unwrap_err();
+    assert_eq!(err.kind, ZephyrErrorKind::UnknownModifier);
+    let err = z.generate_class("c[red]$some-new-element").unwrap_err();
+    assert_eq!(err.kind, ZephyrErrorKind::UnknownPseudo);
+}
+
+#[test]
+fn generate_classes_skips_errors() {
+    let z = Zephyr {
+        strict: true,
+        ..Zephyr::new()
+    };
+
+    let classes = z.generate_classes(["c[red]hovr", "flex", "m[1rem]nth-child(2)", "-webkit-box"]);
+    assert_eq!(
+        classes,
+        r#".flex{display:flex}.m\[1rem\]nth-child\(2\):nth-child(2){margin:1rem}"#
+    );
+}
+
+#[test]
+fn generate_report() {
+    let z = Zephyr {
+        strict: true,
+        ..Zephyr::new()
+    };
+
+    let report =
+        z.generate_classes_report(["flex mt[1rem]", "c[red]hovr flex", "flex mt[1rem] mx"]);
+    assert_eq!(
+        report.css,
+        r#".flex{display:flex}.mt\[1rem\]{margin-top:1rem}"#
+    );
+    assert_eq!(report.classes, ["flex", "mt[1rem]"]);
+    assert_eq!(report.duplicates, ["flex", "flex", "mt[1rem]"]);
+    assert!(report.has_errors());
+    assert_eq!(
+        report
+            .errors
+            .iter()
+            .map(|e| (e.class.as_str(), e.kind.clone()))
+            .collect::<Vec<_>>(),
+        [
+            ("c[red]hovr", ZephyrErrorKind::UnknownModifier),
+            ("mx", ZephyrErrorKind::ValueMissing)
+        ]
+    );
+}
+
+#[test]
+fn generate_grouped_by_media_query() {
+    let z = Zephyr::new();
+
+    let classes = z.generate_classes([
+        "m[1rem]md",
+        "p[1rem]@sm",
+        "c[red]<md",
+        "flex",
+        "p[1rem]sm",
+        "c[blue]<lg",
+        "c[red]motion-reduce",
+        "m[2rem]md",
+        "mt[0]",
+    ]);
+    assert_eq!(
+        classes,
+        [
+            r#".flex{display:flex}"#,
+            r#".mt\[0\]{margin-top:0}"#,
+            r#"@media(prefers-reduced-motion:reduce){.c\[red\]motion-reduce{color:red}}"#,
+            r#"@media(min-width:640px){.p\[1rem\]sm{padding:1rem}}"#,
+            r#"@media(min-width:768px){.m\[1rem\]md{margin:1rem}.m\[2rem\]md{margin:2rem}}"#,
+            r#"@media(max-width:1023.9px){.c\[blue\]\<lg{color:blue}}"#,
+            r#"@media(max-width:767.9px){.c\[red\]\<md{color:red}}"#,
+            r#"@media(min-width:640px)and(max-width:767.9px){.p\[1rem\]\@sm{padding:1rem}}"#,
+        ]
+        .concat()
+    );
+}
+
+#[test]
+fn generate_grouped_pretty() {
+    let mut z = Zephyr::new();
+    z.pretty_print = true;
+
+    let classes = z.generate_classes(["m[1rem]md", "flex", "m[2rem]md"]);
+    assert_eq!(
+        classes,
+        r#".flex {
+    display:flex
+}
+
+@media (min-width:768px) {
+    .m\[1rem\]md {
+        margin: 1rem
+    }
+
+    .m\[2rem\]md {
+        margin: 2rem
+    }
+}
+"#
+    );
+}
+
+#[test]
+fn generate_custom_breakpoints() {
+    let mut z = Zephyr::new();
+    z.breakpoints = vec![
+        Breakpoint::new("tablet", Length::em(48.0)),
+        Breakpoint::new("desktop", Length::em(64.0)),
+        Breakpoint::new("wide", Length::px(1440.0)),
+    ];
+
+    let classes = z.generate_classes(["m[0]wide", "m[1rem]tablet", "m[2rem]@desktop"]);
+    assert_eq!(
+        classes,
+        [
+            r#"@media(min-width:48em){.m\[1rem\]tablet{margin:1rem}}"#,
+            r#"@media(min-width:1440px){.m\[0\]wide{margin:0}}"#,
+            r#"@media(min-width:64em)and(max-width:1439.9px){.m\[2rem\]\@desktop{margin:2rem}}"#,
+        ]
+        .concat()
+    );
+
+    let z = Zephyr { strict: true, ..z };
+    let err = z.generate_class("m[0]tablt").unwrap_err();
+    assert_eq!(err.suggestion.as_deref(), Some("tablet"));
+}
+
+#[test]
+fn generate_arbitrary_media_queries() {
+    let z = Zephyr::new();
+
+    let classes = z.generate_classes([
+        "m[0]h-min-[600px]",
+        "m[1rem]min-[900px],hover",
+        "m[2rem]max-[40em]",
+        "m[3rem]md",
+        "m[4rem]min-[900px]",
+        "m[5rem]max-[40em],motion-safe",
+    ]);
+    assert_eq!(
+        classes,
+        [
+            r#"@media(min-width:768px){.m\[3rem\]md{margin:3rem}}"#,
+            r#"@media(min-width:900px){.m\[1rem\]min-\[900px\]\,hover:hover{margin:1rem}.m\[4rem\]min-\[900px\]{margin:4rem}}"#,
+            r#"@media(max-width:40em){.m\[2rem\]max-\[40em\]{margin:2rem}}"#,
+            r#"@media(max-width:40em)and(prefers-reduced-motion:no-preference){.m\[5rem\]max-\[40em\]\,motion-safe{margin:5rem}}"#,
+            r#"@media(min-height:600px){.m\[0\]h-min-\[600px\]{margin:0}}"#,
+        ]
+        .concat()
+    );
+}
+
+#[test]
+fn generate_combined_responsive_modifiers() {
+    let z = Zephyr::new();
+
+    let classes = z.generate_classes(["p[1rem]md,<xl", "p[0]<xl"]);
+    assert_eq!(
+        classes,
+        [
+            r#"@media(max-width:1279.9px){.p\[0\]\<xl{padding:0}}"#,
+            r#"@media(min-width:768px)and(max-width:1279.9px){.p\[1rem\]md\,\<xl{padding:1rem}}"#,
+        ]
+        .concat()
+    );
+
+    let err = z.generate_class("p[1rem]lg,<sm").unwrap_err();
+    assert_eq!(err.kind, ZephyrErrorKind::ContradictoryModifiers);
+    assert_eq!(err.snippet(), "<sm");
+}
+
+#[test]
+fn generate_media_features() {
+    let mut z = Zephyr::new();
+    z.media_features
+        .insert("tall".to_string(), "(min-height:800px)".to_string());
+
+    let classes = z.generate_classes([
+        "c[white]md,dark",
+        "c[black]print",
+        "c[white]dark",
+        "p[0]tall,landscape",
+        "c[gray]dark,md",
+        "display[none]print,lg",
+    ]);
+    assert_eq!(
+        classes,
+        [
+            r#"@media(min-height:800px)and(orientation:landscape){.p\[0\]tall\,landscape{padding:0}}"#,
+            r#"@media(prefers-color-scheme:dark){.c\[white\]dark{color:white}}"#,
+            r#"@media print{.c\[black\]print{color:black}}"#,
+            r#"@media(min-width:768px)and(prefers-color-scheme:dark){.c\[white\]md\,dark{color:white}.c\[gray\]dark\,md{color:gray}}"#,
+            r#"@media print and (min-width:1024px){.display\[none\]print\,lg{display:none}}"#,
+        ]
+        .concat()
+    );
+
+    let z = Zephyr { strict: true, ..z };
+    let err = z.generate_class("c[white]drak").unwrap_err();
+    assert_eq!(err.suggestion.as_deref(), Some("dark"));
+}
+
+#[test]
+fn generate_container_queries() {
+    let z = Zephyr::new();
+
+    let classes = z.generate_classes([
+        "cq",
+        "cq[sidebar]",
+        "p[1rem]cq-sidebar-md",
+        "p[0]@c-md",
+        "m[0]cq-min-[400px],hover",
+        "m[1rem]lg,cq-min-[400px]",
+        "p[2rem]cq-sidebar-md",
+    ]);
+    assert_eq!(
+        classes,
+        [
+            r#".cq{container-type:inline-size}"#,
+            r#".cq\[sidebar\]{container-type:inline-size;container-name:sidebar}"#,
+            r#"@container(min-width:400px){.m\[0\]cq-min-\[400px\]\,hover:hover{margin:0}}"#,
+            r#"@container(min-width:768px){.p\[0\]\@c-md{padding:0}}"#,
+            r#"@container sidebar (min-width:768px){.p\[1rem\]cq-sidebar-md{padding:1rem}.p\[2rem\]cq-sidebar-md{padding:2rem}}"#,
+            r#"@media(min-width:1024px){@container(min-width:400px){.m\[1rem\]lg\,cq-min-\[400px\]{margin:1rem}}}"#,
+        ]
+        .concat()
+    );
+
+    let err = z.generate_class("p[0]cq-a-md,cq-b-md").unwrap_err();
+    assert_eq!(err.kind, ZephyrErrorKind::ContradictoryModifiers);
+    assert_eq!(err.snippet(), "cq-b-md");
+}
+
+#[test]
+fn generate_container_queries_pretty() {
+    let mut z = Zephyr::new();
+    z.pretty_print = true;
+
+    let classes = z.generate_classes(["m[1rem]lg,cq-card-md"]);
+    assert_eq!(
+        classes,
+        r#"@media (min-width:1024px) {
+    @container card (min-width:768px) {
+        .m\[1rem\]lg\,cq-card-md {
+            margin: 1rem
+        }
+    }
+}
+"#
+    );
+}
+
+#[test]
+fn generate_supports() {
+    let z = Zephyr::new();
+
+    let classes = z.generate_classes([
+        "display[grid]supports-grid",
+        "flex",
+        "gap[1rem]supports-[display:grid],md",
+        "bg[#fff8]supports-backdrop",
+        "gap[2rem]md,supports-[display:grid]",
+        "p[1rem]supports-grid,supports-has,cq-min-[400px]",
+    ]);
+    assert_eq!(
+        classes,
+        [
+            r#".flex{display:flex}"#,
+            r#"@supports((backdrop-filter:blur(0)) or (-webkit-backdrop-filter:blur(0))){.bg\[\#fff8\]supports-backdrop{background:#fff8}}"#,
+            r#"@supports(display:grid){.display\[grid\]supports-grid{display:grid}}"#,
+            r#"@supports(display:grid) and selector(:has(*)){@container(min-width:400px){.p\[1rem\]supports-grid\,supports-has\,cq-min-\[400px\]{padding:1rem}}}"#,
+            r#"@media(min-width:768px){@supports(display:grid){.gap\[1rem\]supports-\[display\:grid\]\,md{gap:1rem}.gap\[2rem\]md\,supports-\[display\:grid\]{gap:2rem}}}"#,
+        ]
+        .concat()
+    );
+}
+
+#[test]
+fn generate_group_and_peer() {
+    let z = Zephyr::new();
+
+    let classes = z.generate_classes([
+        "group",
+        "peer/email",
+        "c[red]group-hover",
+        "c[blue]group-hover/card",
+        "c[green]group-hover,group-focus,first",
+        "c[gray]peer-checked",
+        "c[white]peer-focus/email,hover$before",
+        "c[black]group-odd,peer-invalid",
+    ]);
+    assert_eq!(
+        classes,
+        [
+            r#".group:hover .c\[red\]group-hover{color:red}"#,
+            r#".group\/card:hover .c\[blue\]group-hover\/card{color:blue}"#,
+            r#".group:hover:focus .c\[green\]group-hover\,group-focus\,first:first-child{color:green}"#,
+            r#".peer:checked~.c\[gray\]peer-checked{color:gray}"#,
+            r#".peer\/email:focus~.c\[white\]peer-focus\/email\,hover\$before:hover::before{color:white}"#,
+            r#".group:nth-child(odd) .peer:invalid~.c\[black\]group-odd\,peer-invalid{color:black}"#,
+        ]
+        .concat()
+    );
+
+    let z = Zephyr {
+        pretty_print: true,
+        ..Zephyr::new()
+    };
+    assert_eq!(
+        z.generate_classes(["c[gray]peer-checked"]),
+        ".peer:checked ~ .c\\[gray\\]peer-checked {\n    color: gray\n}\n"
+    );
+
+    let report = z.generate_classes_report(["group", "peer/email", "groups"]);
+    assert_eq!(report.css, "");
+    assert_eq!(report.classes, ["group", "peer/email"]);
+    assert_eq!(report.errors.len(), 1);
+    assert_eq!(z.generate_class("group/card").unwrap(), "");
+
+    let z = Zephyr { strict: true, ..z };
+    let err = z.generate_class("c[red]group-hovr").unwrap_err();
+    assert_eq!(err.kind, ZephyrErrorKind::UnknownModifier);
+    assert_eq!(err.snippet(), "hovr");
+    assert_eq!(err.suggestion.as_deref(), Some("hover"));
+}
+
+#[test]
+fn generate_arbitrary_selectors() {
+    let z = Zephyr::new();
+
+    let classes = z.generate_classes([
+        "m[0][&>p]",
+        "c[red][&_a:hover]",
+        "flex|[.js_&]",
+        "c[blue]hover,[&>li]$after",
+        "c[green]group-focus,[&+&]",
+    ]);
+    assert_eq!(
+        classes,
+        [
+            r#".m\[0\]\[\&\>p\]>p{margin:0}"#,
+            r#".c\[red\]\[\&_a\:hover\] a:hover{color:red}"#,
+            r#".js .flex\|\[\.js_\&\]{display:flex}"#,
+            r#".c\[blue\]hover\,\[\&\>li\]\$after:hover>li::after{color:blue}"#,
+            r#".group:focus .c\[green\]group-focus\,\[\&\+\&\]+.group:focus .c\[green\]group-focus\,\[\&\+\&\]{color:green}"#,
+        ]
+        .concat()
+    );
+
+    let p = z.parse("m[0]hover,[&>p]").unwrap();
+    assert_eq!(p.modifiers.selectors[0].node, "[&>p]");
+
+    let err = z.generate_class("m[0][p]").unwrap_err();
+    assert_eq!(err.kind, ZephyrErrorKind::UnknownModifier);
+    assert_eq!(err.snippet(), "[p]");
+}
+
+#[test]
+fn generate_functional_pseudo_classes() {
+    let z = Zephyr::new();
+
+    let classes = z.generate_classes([
+        "m[0]not(.a,.b)",
+        "c[red]nth-child(3n+1)",
+        "c[blue]nth-[3n+1]",
+        "c[green]has-[img]",
+        "c[gray]not-[.a_.b],hover",
+        "c[white]group-has-[img]",
+    ]);
+    assert_eq!(
+        classes,
+        [
+            r#".m\[0\]not\(\.a\,\.b\):not(.a,.b){margin:0}"#,
+            r#".c\[red\]nth-child\(3n\+1\):nth-child(3n+1){color:red}"#,
+            r#".c\[blue\]nth-\[3n\+1\]:nth-child(3n+1){color:blue}"#,
+            r#".c\[green\]has-\[img\]:has(img){color:green}"#,
+            r#".c\[gray\]not-\[\.a_\.b\]\,hover:not(.a .b):hover{color:gray}"#,
+            r#".group:has(img) .c\[white\]group-has-\[img\]{color:white}"#,
+        ]
+        .concat()
+    );
+
+    let p = z.parse("m[0]not(.a,.b),hover").unwrap();
+    assert_eq!(p.modifiers.pseudo_classes.len(), 2);
+
+    // parametric modifiers and functional pseudo-classes need their argument
+    for class in [
+        "c[red]nth",
+        "m[0]not",
+        "m[0]has,hover",
+        "m[0]nth-child",
+        "m[0]not()",
+        "m[0]nth-[]",
+        "m[0]data-[]",
+        "m[0]group-has-[]",
+    ] {
+        let err = z.generate_class(class).unwrap_err();
+        assert_eq!(err.kind, ZephyrErrorKind::MissingArgument, "{class}");
+    }
+    assert_eq!(
+        z.generate_class("m[0]not").unwrap_err().to_string(),
+        "`not` needs an argument in `m[0]not` at 4..7"
+    );
+    assert_eq!(
+        z.generate_class("c[red]meow-[1]").unwrap_err().snippet(),
+        "meow-[1]"
+    );
+}
+
+#[test]
+fn generate_attribute_modifiers() {
+    let mut z = Zephyr::new();
+    z.modifiers
+        .insert("open".to_string(), "[data-state=open]".to_string());
+
+    let classes = z.generate_classes([
+        "c[red]data-[state=open]",
+        "c[blue]aria-expanded,hover",
+        "c[green]aria-[sort=ascending]",
+        "c[gray]open,md",
+        "c[white]group-aria-expanded",
+    ]);
+    assert_eq!(
+        classes,
+        [
+            r#".c\[red\]data-\[state\=open\][data-state=open]{color:red}"#,
+            r#".c\[blue\]aria-expanded\,hover[aria-expanded="true"]:hover{color:blue}"#,
+            r#".c\[green\]aria-\[sort\=ascending\][aria-sort=ascending]{color:green}"#,
+            r#".group[aria-expanded="true"] .c\[white\]group-aria-expanded{color:white}"#,
+            r#"@media(min-width:768px){.c\[gray\]open\,md[data-state=open]{color:gray}}"#,
+        ]
+        .concat()
+    );
+}
+
+#[test]
+fn generate_escaped_selectors() {
+    let z = Zephyr::new();
+
+    let classes = z.generate_classes(["w[1/2]", "grid-area[1/1/2/2]", "content['!']"]);
+    assert_eq!(
+        classes,
+        [
+            r#".w\[1\/2\]{width:1/2}"#,
+            r#".grid-area\[1\/1\/2\/2\]{grid-area:1/1/2/2}"#,
+            r#".content\[\'\!\'\]{content:'!'}"#,
+        ]
+        .concat()
+    );
+}
+
+#[test]
+fn generate_important() {
+    let z = Zephyr::new();
+
+    let classes = z.generate_classes([
+        "c[red]!",
+        "!c[blue]hover",
+        "mx[1rem]!",
+        "flex!",
+        "wh[0]$after!",
+        "mx[1rem]!$after",
+        "bg[url(data:image/png;base64,AAA)]!",
+        "content[\";\"]!",
+    ]);
+    assert_eq!(
+        classes,
+        [
+            r#".c\[red\]\!{color:red!important}"#,
+            r#".\!c\[blue\]hover:hover{color:blue!important}"#,
+            r#".mx\[1rem\]\!{margin-left:1rem!important;margin-right:1rem!important}"#,
+            r#".flex\!{display:flex!important}"#,
+            r#".wh\[0\]\$after\!::after{width:0!important;height:0!important}"#,
+            r#".mx\[1rem\]\!\$after::after{margin-left:1rem!important;margin-right:1rem!important}"#,
+            r#".bg\[url\(data\:image\/png\;base64\,AAA\)\]\!{background:url(data:image/png;base64,AAA)!important}"#,
+            r#".content\[\"\;\"\]\!{content:";"!important}"#,
+        ]
+        .concat()
+    );
+
+    let z = Zephyr {
+        pretty_print: true,
+        ..Zephyr::new()
+    };
+    assert_eq!(
+        z.generate_classes(["c[red]!"]),
+        ".c\\[red\\]\\! {\n    color: red !important\n}\n"
+    );
+
+    let p = z.parse("!m[0]hover").unwrap();
+    assert!(p.important);
+    assert_eq!(p.property.span, 1..2);
+
+    let err = z.generate_class("!m[0").unwrap_err();
+    assert_eq!(err.span, 2..3);
+    assert_eq!(err.class, "!m[0");
+}
+
+#[test]
+fn generate_negative() {
+    let z = Zephyr::new();
+
+    let classes = z.generate_classes([
+        "-mt[1rem]",
+        "-top[4px]",
+        "-mt(gap)",
+        "-m[calc(1rem_+_2px)]",
+        "-mx[1rem]",
+        "-m[1rem_-2rem]",
+        "-webkit-line-clamp[3]",
+        "-m[-.5rem_0]",
+    ]);
+    assert_eq!(
+        classes,
+        [
+            r#".-mt\[1rem\]{margin-top:-1rem}"#,
+            r#".-top\[4px\]{top:-4px}"#,
+            r#".-mt\(gap\){margin-top:calc(-1 * var(--gap))}"#,
+            r#".-m\[calc\(1rem_\+_2px\)\]{margin:calc(-1 * calc(1rem + 2px))}"#,
+            r#".-mx\[1rem\]{margin-left:-1rem;margin-right:-1rem}"#,
+            r#".-m\[1rem_-2rem\]{margin:-1rem 2rem}"#,
+            r#".-webkit-line-clamp\[3\]{-webkit-line-clamp:3}"#,
+            r#".-m\[-\.5rem_0\]{margin:.5rem -0}"#,
+        ]
+        .concat()
+    );
+
+    let mut z = Zephyr::new();
+    z.add_special("inset-xy", "left:{0};right:{0};top:{1};bottom:{1}");
+    assert_eq!(
+        z.generate_classes(["-inset-xy[1rem,var(--y)]"]),
+        r#".-inset-xy\[1rem\,var\(--y\)\]{left:-1rem;right:-1rem;top:calc(-1 * var(--y));bottom:calc(-1 * var(--y))}"#
+    );
+
+    for (class, value) in [
+        ("-m[auto]", "auto"),
+        ("-m[1rem_auto]", "1rem_auto"),
+        ("-inset-xy[1rem,auto]", "1rem,auto"),
+    ] {
+        let err = z.generate_class(class).unwrap_err();
+        assert_eq!(err.kind, ZephyrErrorKind::NotNegatable);
+        assert_eq!(err.snippet(), value);
+    }
+
+    let p = z.parse("-mt[1rem]").unwrap();
+    assert!(p.negative);
+    assert_eq!(p.property.span, 1..3);
+}
+
+#[test]
+fn generate_variable_fallbacks() {
+    let z = Zephyr::new();
+
+    let classes = z.generate_classes([
+        "c(brand,red)",
+        "c(brand,(accent,red))",
+        "border(b,1px_solid_red)",
+        "-mt(gap,1rem)",
+        "bg(brand,rgb(0,0,0))",
+    ]);
+    assert_eq!(
+        classes,
+        [
+            r#".c\(brand\,red\){color:var(--brand, red)}"#,
+            r#".c\(brand\,\(accent\,red\)\){color:var(--brand, var(--accent, red))}"#,
+            r#".border\(b\,1px_solid_red\){border:var(--b, 1px solid red)}"#,
+            r#".-mt\(gap\,1rem\){margin-top:calc(-1 * var(--gap, 1rem))}"#,
+            r#".bg\(brand\,rgb\(0\,0\,0\)\){background:var(--brand, rgb(0,0,0))}"#,
+        ]
+        .concat()
+    );
+}
+
+#[test]
+fn generate_custom_properties() {
+    let z = Zephyr::new();
+
+    let classes = z.generate_classes([
+        "--brand[#f00]",
+        "set(accent)[#0f0]",
+        "set(c)(brand)hover",
+        "set(brand)[#00f]md,dark",
+    ]);
+    assert_eq!(
+        classes,
+        [
+            r#".--brand\[\#f00\]{--brand:#f00}"#,
+            r#".set\(accent\)\[\#0f0\]{--accent:#0f0}"#,
+            r#".set\(c\)\(brand\)hover:hover{--c:var(--brand)}"#,
+            r#"@media(min-width:768px)and(prefers-color-scheme:dark){.set\(brand\)\[\#00f\]md\,dark{--brand:#00f}}"#,
+        ]
+        .concat()
+    );
+
+    let p = z.parse("set(brand)[#f00]hover").unwrap();
+    assert!(p.custom_property);
+    assert_eq!(p.property.node, "brand");
+    assert_eq!(p.property.span, 4..9);
+    assert_eq!(p.value.unwrap().text, "#f00");
+    assert_eq!(p.modifiers.pseudo_classes[0].node, "hover");
+}
+
+#[test]
+fn generate_variable_prefix() {
+    let z = Zephyr {
+        variable_prefix: "zp-".to_string(),
+        ..Zephyr::new()
+    };
+
+    let classes = z.generate_classes([
+        "c(primary)",
+        "c(brand,(accent,red))",
+        "--brand[#f00]",
+        "set(accent)(brand)",
+    ]);
+    assert_eq!(
+        classes,
+        [
+            r#".c\(primary\){color:var(--zp-primary)}"#,
+            r#".c\(brand\,\(accent\,red\)\){color:var(--zp-brand, var(--zp-accent, red))}"#,
+            r#".--brand\[\#f00\]{--zp-brand:#f00}"#,
+            r#".set\(accent\)\(brand\){--zp-accent:var(--zp-brand)}"#,
+        ]
+        .concat()
+    );
+}
+
+#[test]
+fn generate_theme_tokens() {
+    let mut z = Zephyr::new();
+    z.theme.scales.get_mut("color").unwrap().extend([
+        ("brand-500".to_string(), "#3b82f6".to_string()),
+        ("brand-700".to_string(), "#1d4ed8".to_string()),
+    ]);
+
+    let classes = z.generate_classes([
+        "m[4]",
+        "-mt[0.5]",
+        "mx[px]",
+        "c[brand-500]",
+        "c[red]",
+        "fs[lg]",
+        "m[1rem]",
+        "w[4]",
+        "border-radius[md]",
+        "border-radius[full]",
+    ]);
+    assert_eq!(
+        classes,
+        [
+            r#".m\[4\]{margin:1rem}"#,
+            r#".-mt\[0\.5\]{margin-top:-0.125rem}"#,
+            r#".mx\[px\]{margin-left:1px;margin-right:1px}"#,
+            r#".c\[brand-500\]{color:#3b82f6}"#,
+            r#".c\[red\]{color:red}"#,
+            r#".fs\[lg\]{font-size:1.125rem}"#,
+            r#".m\[1rem\]{margin:1rem}"#,
+            r#".w\[4\]{width:4}"#,
+            r#".border-radius\[md\]{border-radius:0.375rem}"#,
+            // value short-hands come before the theme
+            r#".border-radius\[full\]{border-radius:100%}"#,
+        ]
+        .concat()
+    );
+
+    z.theme.use_variables = true;
+    z.variable_prefix = "zp-".to_string();
+    let classes = z.generate_classes(["m[4]", "-mt[0.5]", "c[brand-700]hover", "c[red]"]);
+    assert_eq!(
+        classes,
+        [
+            r#".m\[4\]{margin:var(--zp-spacing-4)}"#,
+            r#".-mt\[0\.5\]{margin-top:calc(-1 * var(--zp-spacing-0_5))}"#,
+            r#".c\[brand-700\]hover:hover{color:var(--zp-color-brand-700)}"#,
+            r#".c\[red\]{color:red}"#,
+        ]
+        .concat()
+    );
+}
+
+#[test]
+fn generate_theme_variables() {
+    let mut z = Zephyr::new();
+    z.theme.scales = [
+        (
+            "color".to_string(),
+            [("brand-500", "#3b82f6"), ("bg", "white")]
+                .map(|(a, b)| (a.to_string(), b.to_string()))
+                .into(),
+        ),
+        (
+            "spacing".to_string(),
+            [("0.5", "0.125rem")]
+                .map(|(a, b)| (a.to_string(), b.to_string()))
+                .into(),
+        ),
+    ]
+    .into();
+    assert_eq!(
+        z.generate_theme_variables(),
+        ":root{--color-bg:white;--color-brand-500:#3b82f6;--spacing-0_5:0.125rem}"
+    );
+
+    z.theme.dark = [(
+        "color".to_string(),
+        [("bg".to_string(), "black".to_string())].into(),
+    )]
+    .into();
+    z.variable_prefix = "zp-".to_string();
+    assert_eq!(
+        z.generate_theme_variables(),
+        [
+            ":root{--zp-color-bg:white;--zp-color-brand-500:#3b82f6;--zp-spacing-0_5:0.125rem}",
+            "@media(prefers-color-scheme:dark){:root{--zp-color-bg:black}}",
+        ]
+        .concat()
+    );
+    // classes can use the same variables
+    assert_eq!(
+        z.generate_classes(["bg(color-bg)"]),
+        r#".bg\(color-bg\){background:var(--zp-color-bg)}"#
+    );
+
+    z.pretty_print = true;
+    z.theme.scales.remove("spacing");
+    assert_eq!(
+        z.generate_theme_variables(),
+        r#":root {
+    --zp-color-bg: white;
+    --zp-color-brand-500: #3b82f6
+}
+@media (prefers-color-scheme:dark) {
+    :root {
+        --zp-color-bg: black
+    }
+}
+"#
+    );
+
+    z.theme.dark_selector = Some("[data-theme=dark]".to_string());
+    z.pretty_print = false;
+    assert_eq!(
+        z.generate_theme_variables(),
+        ":root{--zp-color-bg:white;--zp-color-brand-500:#3b82f6}[data-theme=dark]{--zp-color-bg:black}"
+    );
+}
@@ -0,0 +1,112 @@
+use std::{borrow::Cow, collections::HashMap};
+
+use crate::{indent, nl, space};
+
+/// design tokens, grouped into named scales
+///
+/// each property can be bound to a scale, so its values are looked up as tokens of that scale:
+/// with `margin` bound to `spacing`, `m[4]` results in `margin:1rem`.
+/// values that aren't tokens of the scale are used as they are
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Theme {
+    /// scale -> token -> value, eg: `spacing` -> `4` -> `1rem`
+    ///
+    /// the default scales are `spacing`, `font-size`, `radius`, `shadow`, `z-index` and `color`
+    pub scales: HashMap<String, HashMap<String, String>>,
+    /// property -> scale, eg: `margin` -> `spacing`
+    ///
+    /// the properties are the full names, after replacing the short-hands.
+    /// specials, like `mx`, are bound by their own name
+    pub bindings: HashMap<String, String>,
+    /// use `var(--scale-token)` instead of the value of the token, eg: `var(--spacing-4)`
+    ///
+    /// the variables can be defined with [`Zephyr::generate_theme_variables`](crate::Zephyr::generate_theme_variables)
+    pub use_variables: bool,
+    /// tokens that change in dark mode, with the same shape as `scales`
+    pub dark: HashMap<String, HashMap<String, String>>,
+    /// selector for the dark mode variables, eg: `[data-theme=dark]`
+    ///
+    /// when it's `None`, they are applied with `prefers-color-scheme: dark` instead
+    pub dark_selector: Option<String>,
+}
+
+impl Theme {
+    /// resolves a token for a property, if the property is bound to a scale that has it
+    pub(crate) fn resolve<'a>(
+        &'a self,
+        property: &str,
+        token: &str,
+        prefix: &str,
+    ) -> Option<Cow<'a, str>> {
+        let scale = self.bindings.get(property)?;
+        let value = self.scales.get(scale)?.get(token)?;
+        if self.use_variables {
+            Some(format!("var(--{prefix}{})", variable_name(scale, token)).into())
+        } else {
+            Some(value.into())
+        }
+    }
+}
+
+/// a block that defines a variable for every token in the scales, sorted by scale and token
+///
+/// returns an empty string if there are no tokens
+pub(crate) fn variables_block(
+    selector: &str,
+    scales: &HashMap<String, HashMap<String, String>>,
+    prefix: &str,
+    pretty: bool,
+    level: usize,
+) -> String {
+    let mut tokens = scales
+        .iter()
+        .flat_map(|(scale, tokens)| tokens.iter().map(move |(t, v)| (scale, t, v)))
+        .collect::<Vec<_>>();
+    if tokens.is_empty() {
+        return String::new();
+    }
+    tokens.sort();
+
+    let space = space(pretty);
+    let nl = nl(pretty);
+    let indent2 = indent(pretty, level + 1);
+    let indent = indent(pretty, level);
+    let declarations = tokens
+        .into_iter()
+        .map(|(scale, token, value)| {
+            format!(
+                "{indent2}--{prefix}{}:{space}{value}",
+                variable_name(scale, token)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(&format!(";{nl}"));
+    format!("{indent}{selector}{space}{{{nl}{declarations}{nl}{indent}}}{nl}")
+}
+
+/// the name of the variable for a token, without the leading `--`, eg: `spacing-0_5`
+///
+/// dots aren't allowed in variable names without escaping, so they become underscores
+pub(crate) fn variable_name(scale: &str, token: &str) -> String {
+    format!("{scale}-{}", token.replace('.', "_"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Zephyr;
+
+    #[test]
+    fn resolve_tokens() {
+        let mut theme = Zephyr::new().theme;
+        assert_eq!(theme.resolve("margin", "4", "").as_deref(), Some("1rem"));
+        assert_eq!(theme.resolve("margin", "1rem", "").as_deref(), None);
+        assert_eq!(theme.resolve("color", "4", "").as_deref(), None);
+        assert_eq!(theme.resolve("meow", "4", "").as_deref(), None);
+
+        theme.use_variables = true;
+        assert_eq!(
+            theme.resolve("padding-top", "0.5", "zp-").as_deref(),
+            Some("var(--zp-spacing-0_5)")
+        );
+    }
+}
@@ -0,0 +1,459 @@
+//! loading design tokens in the [W3C Design Tokens](https://tr.designtokens.org/format/) format,
+//! like the ones exported from Figma
+
+use std::{collections::HashMap, fmt};
+
+use serde_json::{Map, Value};
+
+use crate::Zephyr;
+
+/// an error found while loading design tokens
+#[derive(Debug)]
+pub enum TokensError {
+    /// the tokens aren't valid json
+    Json(serde_json::Error),
+    /// an alias points to a token that doesn't exist
+    UnknownAlias { token: String, alias: String },
+    /// an alias ends up pointing to the token itself
+    CircularAlias(String),
+}
+
+impl fmt::Display for TokensError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokensError::Json(e) => write!(f, "invalid tokens: {e}"),
+            TokensError::UnknownAlias { token, alias } => {
+                write!(f, "`{token}` refers to `{{{alias}}}`, which doesn't exist")
+            }
+            TokensError::CircularAlias(token) => write!(f, "`{token}` refers to itself"),
+        }
+    }
+}
+
+impl std::error::Error for TokensError {}
+
+impl From<serde_json::Error> for TokensError {
+    fn from(e: serde_json::Error) -> Self {
+        TokensError::Json(e)
+    }
+}
+
+/// a token, before resolving its aliases
+struct Token<'a> {
+    path: Vec<&'a str>,
+    /// the `$type` of the token, or of the closest group that has one
+    kind: Option<&'a str>,
+    value: &'a Value,
+}
+
+/// where a token goes
+#[derive(PartialEq, Eq, Hash, Clone, Copy)]
+enum Target<'a> {
+    /// a scale of the theme
+    Scale(&'a str),
+    /// context-aware values of these properties
+    Properties(&'static [&'static str]),
+    /// values for every property
+    Values,
+}
+
+impl Zephyr {
+    /// loads design tokens, like `{"color": {"brand": {"500": {"$value": "#3b82f6", "$type": "color"}}}}`
+    ///
+    /// the name of a token is its path joined with `-`, like `size-4`. when the top-level group
+    /// is the scale the token goes to, it's left out, so `color.brand.500` is `brand-500`.
+    /// aliases, like `{color.brand.500}`, are replaced by the value they point to.
+    ///
+    /// tokens are added to the scale of [`Zephyr::theme`] named like their top-level group, if there's one.
+    /// otherwise, their `$type` decides where they go:
+    /// - `color`, `dimension` and `shadow` go to the `color`, `spacing` and `shadow` scales
+    /// - `fontFamily`, `fontWeight`, `duration`, `cubicBezier`, `border`, `strokeStyle`, `transition`
+    ///   and `gradient` become context-aware values of the properties that take them
+    /// - anything else becomes a value for every property
+    ///
+    /// tokens that can't be a single css value, like `typography`, are skipped.
+    /// if any alias can't be resolved, nothing is loaded
+    pub fn load_tokens(&mut self, json: &str) -> Result<(), TokensError> {
+        let root: Value = serde_json::from_str(json)?;
+        let mut tokens = vec![];
+        if let Value::Object(groups) = &root {
+            collect(groups, &mut vec![], None, &mut tokens);
+        }
+        let by_path = tokens
+            .iter()
+            .enumerate()
+            .map(|(i, t)| (t.path.join("."), i))
+            .collect::<HashMap<_, _>>();
+
+        // everything is resolved before changing anything, so an error doesn't leave the tokens half-loaded
+        let mut resolved = vec![];
+        for token in &tokens {
+            let path = token.path.join(".");
+            let (value, kind) = resolve(token, &tokens, &by_path, &mut vec![path.clone()])?;
+            let Some(value) = value else {
+                tracing::warn!("skipping `{path}`, its value can't be used in a class");
+                continue;
+            };
+
+            let group = token.path[0];
+            let target = if self.theme.scales.contains_key(group) {
+                Target::Scale(group)
+            } else {
+                match kind {
+                    Some("color") => Target::Scale("color"),
+                    Some("dimension") => Target::Scale("spacing"),
+                    Some("shadow") => Target::Scale("shadow"),
+                    Some("fontFamily") => Target::Properties(&["font-family"]),
+                    Some("fontWeight") => Target::Properties(&["font-weight"]),
+                    Some("duration") => {
+                        Target::Properties(&["transition-duration", "animation-duration"])
+                    }
+                    Some("cubicBezier") => Target::Properties(&[
+                        "transition-timing-function",
+                        "animation-timing-function",
+                    ]),
+                    Some("border") => Target::Properties(&["border"]),
+                    Some("strokeStyle") => Target::Properties(&["border-style"]),
+                    Some("transition") => Target::Properties(&["transition"]),
+                    Some("gradient") => Target::Properties(&["background", "background-image"]),
+                    _ => Target::Values,
+                }
+            };
+            // the top-level group is only left out when it's the scale, like in `color.brand.500`,
+            // so tokens from different groups don't end up with the same name
+            let name = match &token.path[..] {
+                [g, rest @ ..] if !rest.is_empty() && target == Target::Scale(g) => rest.join("-"),
+                _ => token.path.join("-"),
+            };
+            resolved.push((path, target, name, value));
+        }
+
+        let mut seen = HashMap::new();
+        for (path, target, name, value) in resolved {
+            if let Some(other) = seen.insert((target, name.clone()), path.clone()) {
+                tracing::warn!("`{path}` overwrites `{other}`, they are both named `{name}`");
+            }
+
+            match target {
+                Target::Scale(scale) => {
+                    self.theme
+                        .scales
+                        .entry(scale.to_string())
+                        .or_default()
+                        .insert(name, value);
+                }
+                Target::Properties(properties) => {
+                    for p in properties {
+                        self.context_aware_values
+                            .entry(p.to_string())
+                            .or_default()
+                            .insert(name.clone(), value.clone());
+                    }
+                }
+                Target::Values => {
+                    self.values.insert(name, value);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// finds all the tokens inside of the groups
+fn collect<'a>(
+    groups: &'a Map<String, Value>,
+    path: &mut Vec<&'a str>,
+    kind: Option<&'a str>,
+    tokens: &mut Vec<Token<'a>>,
+) {
+    for (name, v) in groups {
+        // `$type`, `$description` and the like
+        if name.starts_with('$') {
+            continue;
+        }
+        let Value::Object(v) = v else {
+            continue;
+        };
+
+        path.push(name);
+        let kind = v.get("$type").and_then(Value::as_str).or(kind);
+        match v.get("$value") {
+            Some(value) => tokens.push(Token {
+                path: path.clone(),
+                kind,
+                value,
+            }),
+            None => collect(v, path, kind, tokens),
+        }
+        path.pop();
+    }
+}
+
+/// resolves the css value of a token, along with its type, which can come from an alias
+///
+/// `stack` holds the tokens that are being resolved, to catch circular aliases
+fn resolve<'a>(
+    token: &Token<'a>,
+    tokens: &[Token<'a>],
+    by_path: &HashMap<String, usize>,
+    stack: &mut Vec<String>,
+) -> Result<(Option<String>, Option<&'a str>), TokensError> {
+    let mut alias = |a: &str| {
+        let t = by_path
+            .get(a)
+            .map(|i| &tokens[*i])
+            .ok_or_else(|| TokensError::UnknownAlias {
+                token: stack[0].clone(),
+                alias: a.to_string(),
+            })?;
+        if stack.iter().any(|s| s == a) {
+            return Err(TokensError::CircularAlias(stack[0].clone()));
+        }
+        stack.push(a.to_string());
+        let r = resolve(t, tokens, by_path, stack);
+        stack.pop();
+        r
+    };
+
+    // a value that is just an alias also takes the type of the token it points to
+    if let Some(a) = token
+        .value
+        .as_str()
+        .and_then(|s| s.strip_prefix('{')?.strip_suffix('}'))
+        .filter(|a| !a.contains(['{', '}']))
+    {
+        let (value, kind) = alias(a)?;
+        return Ok((value, token.kind.or(kind)));
+    }
+
+    Ok((css(token.value, token.kind, &mut alias)?, token.kind))
+}
+
+type Alias<'a, 'b> = dyn FnMut(&str) -> Result<(Option<String>, Option<&'a str>), TokensError> + 'b;
+
+/// turns the value of a token into css, or `None` if it can't be a single value
+fn css(
+    value: &Value,
+    kind: Option<&str>,
+    alias: &mut Alias<'_, '_>,
+) -> Result<Option<String>, TokensError> {
+    let field = |o: &Map<String, Value>, k: &str, alias: &mut Alias<'_, '_>| match o.get(k) {
+        Some(v) => css(v, None, alias),
+        None => Ok(None),
+    };
+
+    let css = match (kind, value) {
+        (_, Value::String(s)) => Some(replace_aliases(s, alias)?),
+        (_, Value::Number(n)) => Some(n.to_string()),
+        (Some("cubicBezier"), Value::Array(a)) => {
+            let points = a.iter().map(ToString::to_string).collect::<Vec<_>>();
+            Some(format!("cubic-bezier({})", points.join(", ")))
+        }
+        (Some("gradient"), Value::Array(stops)) => {
+            let mut r = vec![];
+            for s in stops {
+                let Value::Object(s) = s else {
+                    return Ok(None);
+                };
+                let color = field(s, "color", alias)?.unwrap_or_default();
+                let position = s.get("position").and_then(Value::as_f64).unwrap_or(0.0);
+                r.push(format!("{color} {}%", position * 100.0));
+            }
+            Some(format!("linear-gradient({})", r.join(", ")))
+        }
+        (_, Value::Array(a)) => {
+            let mut r = vec![];
+            for v in a {
+                match css(v, kind, alias)? {
+                    Some(v) => r.push(v),
+                    None => return Ok(None),
+                }
+            }
+            Some(r.join(", "))
+        }
+        // `{"value": 4, "unit": "px"}`
+        (_, Value::Object(o)) if o.contains_key("unit") => {
+            let value = field(o, "value", alias)?.unwrap_or_default();
+            let unit = field(o, "unit", alias)?.unwrap_or_default();
+            Some(format!("{value}{unit}"))
+        }
+        (Some("shadow"), Value::Object(o)) => {
+            let mut r = vec![];
+            if o.get("inset").and_then(Value::as_bool) == Some(true) {
+                r.push("inset".to_string());
+            }
+            for k in ["offsetX", "offsetY", "blur", "spread", "color"] {
+                r.extend(field(o, k, alias)?);
+            }
+            Some(r.join(" "))
+        }
+        (Some("border"), Value::Object(o)) => {
+            let mut r = vec![];
+            for k in ["width", "style", "color"] {
+                r.extend(field(o, k, alias)?);
+            }
+            Some(r.join(" "))
+        }
+        (Some("transition"), Value::Object(o)) => {
+            let mut r = vec![];
+            for k in ["duration", "timingFunction", "delay"] {
+                r.extend(field(o, k, alias)?);
+            }
+            Some(r.join(" "))
+        }
+        _ => None,
+    };
+    Ok(css)
+}
+
+/// replaces the aliases inside of a string, like `1px solid {color.border}`
+fn replace_aliases(s: &str, alias: &mut Alias<'_, '_>) -> Result<String, TokensError> {
+    let mut r = String::new();
+    let mut rest = s;
+    while let Some((before, after)) = rest.split_once('{') {
+        let Some((a, after)) = after.split_once('}') else {
+            break;
+        };
+        r.push_str(before);
+        r.push_str(&alias(a)?.0.unwrap_or_default());
+        rest = after;
+    }
+    r.push_str(rest);
+    Ok(r)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOKENS: &str = r##"{
+        "color": {
+            "$type": "color",
+            "brand": {
+                "500": { "$value": "#3b82f6" },
+                "700": { "$value": "#1d4ed8", "$description": "darker" }
+            },
+            "primary": { "$value": "{color.brand.500}" }
+        },
+        "size": {
+            "$type": "dimension",
+            "4": { "$value": "1rem" },
+            "8": { "$value": { "value": 2, "unit": "rem" } }
+        },
+        "radius": {
+            "card": { "$value": "{size.4}" }
+        },
+        "font": {
+            "sans": { "$type": "fontFamily", "$value": ["Inter", "sans-serif"] },
+            "bold": { "$type": "fontWeight", "$value": 700 },
+            "heading": { "$type": "typography", "$value": { "fontSize": "2rem" } }
+        },
+        "ease": { "$type": "cubicBezier", "out": { "$value": [0, 0, 0.58, 1] } },
+        "shadow": {
+            "$type": "shadow",
+            "card": {
+                "$value": {
+                    "color": "{color.brand.700}",
+                    "offsetX": "0",
+                    "offsetY": "1px",
+                    "blur": "2px",
+                    "spread": "0"
+                }
+            }
+        },
+        "line": { "border": { "$type": "border", "$value": { "width": "1px", "style": "solid", "color": "{color.primary}" } } },
+        "opacity": { "half": { "$value": 0.5 } }
+    }"##;
+
+    #[test]
+    fn load_tokens() {
+        let mut z = Zephyr::new();
+        z.load_tokens(TOKENS).unwrap();
+
+        let color = &z.theme.scales["color"];
+        assert_eq!(color["brand-500"], "#3b82f6");
+        assert_eq!(color["brand-700"], "#1d4ed8");
+        assert_eq!(color["primary"], "#3b82f6");
+
+        // the group isn't the scale, so it stays in the name
+        let spacing = &z.theme.scales["spacing"];
+        assert_eq!(spacing["size-4"], "1rem");
+        assert_eq!(spacing["size-8"], "2rem");
+        // named after the top-level group, and typed through the alias
+        assert_eq!(z.theme.scales["radius"]["card"], "1rem");
+        assert_eq!(z.theme.scales["shadow"]["card"], "0 1px 2px 0 #1d4ed8");
+
+        assert_eq!(
+            z.context_aware_values["font-family"]["font-sans"],
+            "Inter, sans-serif"
+        );
+        assert_eq!(z.context_aware_values["font-weight"]["font-bold"], "700");
+        assert_eq!(
+            z.context_aware_values["transition-timing-function"]["ease-out"],
+            "cubic-bezier(0, 0, 0.58, 1)"
+        );
+        assert_eq!(
+            z.context_aware_values["border"]["line-border"],
+            "1px solid #3b82f6"
+        );
+        assert_eq!(z.values["opacity-half"], "0.5");
+        assert!(!z.values.contains_key("font-heading"));
+
+        assert_eq!(
+            z.generate_classes(["c[primary]", "fw[font-bold]", "m[size-8]"]),
+            r#".c\[primary\]{color:#3b82f6}.fw\[font-bold\]{font-weight:700}.m\[size-8\]{margin:2rem}"#
+        );
+
+        // groups that aren't scales don't overwrite each other
+        let mut z = Zephyr::new();
+        z.load_tokens(
+            r##"{
+                "blue": { "$type": "color", "500": { "$value": "#3b82f6" } },
+                "red": { "$type": "color", "500": { "$value": "#ef4444" } }
+            }"##,
+        )
+        .unwrap();
+        let color = &z.theme.scales["color"];
+        assert_eq!(color["blue-500"], "#3b82f6");
+        assert_eq!(color["red-500"], "#ef4444");
+        assert!(!color.contains_key("500"));
+        assert_eq!(
+            z.generate_classes(["c[blue-500]"]),
+            r#".c\[blue-500\]{color:#3b82f6}"#
+        );
+    }
+
+    #[test]
+    fn alias_errors() {
+        let mut z = Zephyr::new();
+        let err = z.load_tokens(r#"{"a": {"$value": "{b.c}"}}"#).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "`a` refers to `{b.c}`, which doesn't exist"
+        );
+
+        let err = z
+            .load_tokens(r#"{"a": {"$value": "{b}"}, "b": {"$value": "{a}"}}"#)
+            .unwrap_err();
+        assert!(matches!(err, TokensError::CircularAlias(_)));
+
+        assert!(matches!(z.load_tokens("{"), Err(TokensError::Json(_))));
+    }
+
+    #[test]
+    fn errors_load_nothing() {
+        let mut z = Zephyr::new();
+        let values = z.values.clone();
+        let scales = z.theme.scales.clone();
+
+        assert!(z
+            .load_tokens(r#"{"a": {"$value": "1px"}, "b": {"$value": "{zzz}"}}"#)
+            .is_err());
+        assert!(z
+            .load_tokens(r##"{"color": {"$type": "color", "red": {"$value": "#f00"}}, "c": {"$value": "{c}"}}"##)
+            .is_err());
+        assert_eq!(z.values, values);
+        assert_eq!(z.theme.scales, scales);
+    }
+}
@@ -4,11 +4,11 @@ use std::{
 };
 
 use clap::Parser;
-use color_eyre::eyre::Result;
+use color_eyre::eyre::{eyre, Result};
 use notify::RecursiveMode;
 use notify_debouncer_mini::new_debouncer;
 use walkdir::{self, WalkDir};
-use zephyr::{scraping::*, Zephyr};
+use zephyr::{config::Config, scraping::*, Zephyr};
 
 /// generate css :)
 #[derive(Parser, Debug)]
@@ -28,6 +28,16 @@ struct Args {
     /// disables recursion into subdirectories
     #[clap(short, long, value_parser)]
     no_recurse: bool,
+    /// exit with an error if any class fails to generate, including the ones with unknown
+    /// pseudo-classes or pseudo-elements. ignored when watching
+    #[clap(short, long, value_parser)]
+    strict: bool,
+    /// design tokens to use, in the W3C design tokens json format
+    #[clap(short, long, value_parser)]
+    tokens: Option<PathBuf>,
+    /// config file, in toml or json. defaults to `zephyr.toml` if it exists
+    #[clap(short, long, value_parser)]
+    config: Option<PathBuf>,
 }
 
 fn main() -> Result<()> {
@@ -39,11 +49,26 @@ fn main() -> Result<()> {
     std::fs::write(&output, "")?;
     let output_canonical = output.canonicalize()?;
 
-    let z = Zephyr::new();
+    let config = args.config.or_else(|| {
+        let default = PathBuf::from("zephyr.toml");
+        default.is_file().then_some(default)
+    });
+    let mut z = match config {
+        Some(path) => Zephyr::from_config(Config::from_path(path)?)?,
+        None => Zephyr::new(),
+    };
+    z.strict = args.strict;
+    if let Some(tokens) = &args.tokens {
+        z.load_tokens(&std::fs::read_to_string(tokens)?)?;
+    }
 
-    run(&z, &args.path, &output, args.regex, args.no_recurse)?;
+    let errors = run(&z, &args.path, &output, args.regex, args.no_recurse)?;
     println!("generated {}", output.as_os_str().to_string_lossy());
 
+    if args.strict && !args.watch && errors > 0 {
+        return Err(eyre!("{errors} classes failed to generate"));
+    }
+
     if args.watch {
         let (tx, rx) = std::sync::mpsc::channel();
         let mut debouncer = new_debouncer(Duration::from_secs(3), None, tx).unwrap();
@@ -73,7 +98,8 @@ fn main() -> Result<()> {
     Ok(())
 }
 
-fn run(z: &Zephyr, source: &Path, output: &Path, regex: bool, no_recurse: bool) -> Result<()> {
+/// generates the css, and returns how many classes failed
+fn run(z: &Zephyr, source: &Path, output: &Path, regex: bool, no_recurse: bool) -> Result<usize> {
     let mut files = vec![];
     if source.is_dir() {
         let mut w = WalkDir::new(source).follow_links(true);
@@ -107,9 +133,12 @@ fn run(z: &Zephyr, source: &Path, output: &Path, regex: bool, no_recurse: bool)
         })
         .collect::<Vec<_>>();
 
-    let css = z.generate_classes(classes.iter().map(String::as_str));
+    let report = z.generate_classes_report(classes.iter().map(String::as_str));
+    for err in &report.errors {
+        eprintln!("error: {err}");
+    }
 
-    std::fs::write(output, css)?;
+    std::fs::write(output, report.css)?;
 
-    Ok(())
+    Ok(report.errors.len())
 }